@@ -2,8 +2,12 @@
 //! CLI argument parsing
 //!
 
-use clap::{Parser, ValueEnum};
-use clap_verbosity_flag::Verbosity;
+use crate::lines::{BACKTICK, DOUBLE_QUOTE, SINGLE_QUOTE};
+use crate::sniff::DEFAULT_SAMPLE_LINES;
+use clap::builder::{NonEmptyStringValueParser, RangedU64ValueParser};
+use clap::{Parser, Subcommand, ValueEnum};
+use clap_verbosity_flag::{Verbosity, WarnLevel};
+use regex::Regex;
 use std::fmt;
 use std::path::PathBuf;
 
@@ -11,36 +15,193 @@ use std::path::PathBuf;
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct CliArgs {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// The output table format
     #[arg(short, long, value_enum, default_value_t = TableOutputFmt::Csv)]
     pub format: TableOutputFmt,
 
-    /// The file path to read
+    /// The file path to read, or '-' for stdin [default: stdin]
     #[arg(short, long)]
-    pub input: PathBuf,
+    pub input: Option<PathBuf>,
 
-    /// The output file path
+    /// The output file path, or '-' for stdout [default: stdout]
     #[arg(short, long)]
-    pub output: PathBuf,
+    pub output: Option<PathBuf>,
+
+    /// The input table format
+    #[arg(long, value_enum, default_value_t = TableInputFmt::Delimited)]
+    pub input_format: TableInputFmt,
 
     /// The delimiter to use
     #[arg(short, long, value_enum, default_values_t = [Delimiter::Space])]
     pub delimiters: Vec<Delimiter>,
 
+    /// A literal string delimiter, which may be repeated (e.g. ';', '::', ' | ')
+    #[arg(long, conflicts_with_all = ["delimiters", "delimiter_regex"])]
+    pub delimiter_str: Vec<String>,
+
+    /// A regular expression delimiter (e.g. ' {2,}' for two or more spaces)
+    #[arg(long, value_parser = Regex::new, conflicts_with = "delimiters")]
+    pub delimiter_regex: Option<Regex>,
+
     /// Whether to treat contiguous delimiter as a single delimiter
     #[arg(long)]
     pub contiguous_delimiters: bool,
 
-    /// Whether to treat text in double-quotes as a single field
+    /// Whether to treat text in quotes as a single field
     #[arg(long)]
     pub quoted_fields: bool,
 
-    /// Whether the first row should be treated as a header
+    /// The characters that can quote a field
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [QuoteChar::Double])]
+    pub quote_chars: Vec<QuoteChar>,
+
+    /// How a quote character is escaped within a quoted field
+    #[arg(long, value_enum, default_value_t = QuoteEscape::Doubled)]
+    pub quote_escape: QuoteEscape,
+
+    /// Whether to keep the surrounding quotes in quoted fields
+    #[arg(long)]
+    pub keep_quotes: bool,
+
+    /// The number of lines to skip at the start of the input, e.g. a title
+    #[arg(long, default_value_t = 0)]
+    pub skip_lines: usize,
+
+    /// The number of lines to skip at the end of the input, e.g. a row count
+    #[arg(long, default_value_t = 0)]
+    pub skip_footer: usize,
+
+    /// Skip lines starting with this prefix, ignoring leading whitespace (e.g. '#')
+    #[arg(long, value_parser = NonEmptyStringValueParser::new())]
+    pub comment_prefix: Option<String>,
+
+    /// Whether to skip lines that are empty or only whitespace
     #[arg(long)]
+    pub skip_blank_lines: bool,
+
+    /// Treat the first row as a header [default: auto-detect]
+    #[arg(long, conflicts_with = "no_header")]
     pub has_header: bool,
 
+    /// Do not treat the first row as a header [default: auto-detect]
+    #[arg(long)]
+    pub no_header: bool,
+
+    /// Whether to detect the delimiters and quoting from the first lines of the input
+    #[arg(long)]
+    pub auto: bool,
+
+    /// The number of lines sampled to detect the delimiters and quoting
+    #[arg(long, default_value_t = DEFAULT_SAMPLE_LINES, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub sample_lines: usize,
+
+    /// Character offsets at which each fixed-width column starts [default: auto-detect]
+    #[arg(long, value_delimiter = ',', conflicts_with = "header_columns")]
+    pub column_offsets: Vec<usize>,
+
+    /// Whether to start fixed-width columns at the words in the header that follow two or more
+    /// spaces, or a gap in the data
+    #[arg(long)]
+    pub header_columns: bool,
+
+    /// The 0-based index of the table to read from an HTML document
+    #[arg(long, default_value_t = 0, conflicts_with = "table_id")]
+    pub table_index: usize,
+
+    /// The id of the table to read from an HTML document
+    #[arg(long)]
+    pub table_id: Option<String>,
+
+    /// How lines that cannot be split into fields are handled
+    #[arg(long, value_enum, default_value_t = OnError::Fail)]
+    pub on_error: OnError,
+
+    /// How rows with a different number of fields to the first row are handled
+    #[arg(long, value_enum, default_value_t = Ragged::Pad)]
+    pub ragged: Ragged,
+
+    /// A file to write lines that cannot be split into fields, with their line numbers and errors
+    #[arg(long)]
+    pub rejects: Option<PathBuf>,
+
+    /// The layout of the JSON output
+    #[arg(long, value_enum, default_value_t = JsonLayout::Records)]
+    pub json_layout: JsonLayout,
+
+    /// Whether to write numbers and booleans as typed values rather than strings in JSON Lines
+    /// output
+    #[arg(long)]
+    pub infer_types: bool,
+
+    /// Whether to output a complete HTML document rather than only the table element
+    #[arg(long)]
+    pub html_standalone: bool,
+
+    /// The CSS theme embedded in a standalone HTML document
+    #[arg(long, value_enum, default_value_t = HtmlTheme::None)]
+    pub html_theme: HtmlTheme,
+
+    /// The caption of the HTML table, also used as the title of a standalone document
+    #[arg(long)]
+    pub html_caption: Option<String>,
+
+    /// The id attribute of the HTML table element
+    #[arg(long)]
+    pub html_id: Option<String>,
+
+    /// The class attribute of the HTML table element
+    #[arg(long)]
+    pub html_class: Option<String>,
+
+    /// The SQL dialect of the SQL output
+    #[arg(long, value_enum, default_value_t = SqlDialect::Generic)]
+    pub sql_dialect: SqlDialect,
+
+    /// How SQL INSERT statements handle rows that conflict with existing rows. Unless conflicts are
+    /// errors, the first column is created as the primary key, if the table does not already exist
+    #[arg(long, value_enum, default_value_t = SqlOnConflict::Error)]
+    pub sql_on_conflict: SqlOnConflict,
+
+    /// Values, in addition to empty values, that are written as NULL in SQL output
+    #[arg(long, value_delimiter = ',')]
+    pub null_values: Vec<String>,
+
+    /// The table name for SQL and TOML output [default: input file name]
+    #[arg(long)]
+    pub table_name: Option<String>,
+
+    /// The schema of the table for SQL output
+    #[arg(long)]
+    pub schema: Option<String>,
+
+    /// The maximum number of rows in each SQL INSERT statement
+    #[arg(long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub batch_size: Option<usize>,
+
+    /// Whether to wrap SQL output in a transaction
+    #[arg(long)]
+    pub transaction: bool,
+
     #[clap(flatten)]
-    pub verbose: Verbosity,
+    pub verbose: Verbosity<WarnLevel>,
+}
+
+/// Subcommands that replace the default conversion of a table
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Reformat every table in markdown documents in place
+    FmtMd {
+        /// The markdown files to reformat
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Do not write the files, and exit with an error if any file would change
+        #[arg(long)]
+        check: bool,
+    },
 }
 
 /// Possible table output formats
@@ -52,8 +213,16 @@ pub enum TableOutputFmt {
     Md,
     /// HTML
     Html,
+    /// Standalone HTML document with a sortable, filterable table
+    HtmlInteractive,
     /// JSON
     Json,
+    /// JSON Lines, one object per row
+    Jsonl,
+    /// YAML sequence of mappings, one per row
+    Yaml,
+    /// TOML array of tables, one per row
+    Toml,
     /// SQL DDL file
     Sql,
 }
@@ -64,12 +233,178 @@ impl fmt::Display for TableOutputFmt {
             TableOutputFmt::Csv => write!(f, "Comma Separated Variable (.csv)"),
             TableOutputFmt::Md => write!(f, "Markdown (.md)"),
             TableOutputFmt::Html => write!(f, "HTML (.html)"),
+            TableOutputFmt::HtmlInteractive => write!(f, "Interactive HTML (.html)"),
             TableOutputFmt::Json => write!(f, "JSON (.json)"),
+            TableOutputFmt::Jsonl => write!(f, "JSON Lines (.jsonl)"),
+            TableOutputFmt::Yaml => write!(f, "YAML (.yaml)"),
+            TableOutputFmt::Toml => write!(f, "TOML (.toml)"),
             TableOutputFmt::Sql => write!(f, "SQL DDL file (.sql)"),
         }
     }
 }
 
+/// Possible table input formats
+#[derive(Debug, Clone, ValueEnum)]
+pub enum TableInputFmt {
+    /// Fields separated by delimiters
+    Delimited,
+    /// Column-aligned fields at fixed character offsets
+    FixedWidth,
+    /// Box-drawn table with ASCII or Unicode borders, e.g. psql or mysql client output
+    Boxed,
+    /// Markdown pipe table
+    Markdown,
+    /// Table element in an HTML document
+    Html,
+}
+
+impl fmt::Display for TableInputFmt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TableInputFmt::Delimited => write!(f, "Delimited"),
+            TableInputFmt::FixedWidth => write!(f, "Fixed width"),
+            TableInputFmt::Boxed => write!(f, "Boxed"),
+            TableInputFmt::Markdown => write!(f, "Markdown"),
+            TableInputFmt::Html => write!(f, "HTML"),
+        }
+    }
+}
+
+/// Possible JSON output layouts
+#[derive(Debug, Clone, ValueEnum)]
+pub enum JsonLayout {
+    /// Array of rows (objects keyed by header if present)
+    Records,
+    /// Object of column arrays keyed by header
+    Columns,
+    /// Object with separate "columns" and "rows" arrays
+    Split,
+}
+
+impl fmt::Display for JsonLayout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonLayout::Records => write!(f, "Records"),
+            JsonLayout::Columns => write!(f, "Columns"),
+            JsonLayout::Split => write!(f, "Split"),
+        }
+    }
+}
+
+/// Possible SQL dialects
+#[derive(Debug, Clone, ValueEnum)]
+pub enum SqlDialect {
+    /// Generic ANSI SQL
+    Generic,
+    /// PostgreSQL
+    Postgres,
+    /// MySQL / MariaDB
+    Mysql,
+    /// SQLite
+    Sqlite,
+    /// Microsoft SQL Server
+    Mssql,
+}
+
+impl fmt::Display for SqlDialect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SqlDialect::Generic => write!(f, "Generic SQL"),
+            SqlDialect::Postgres => write!(f, "PostgreSQL"),
+            SqlDialect::Mysql => write!(f, "MySQL"),
+            SqlDialect::Sqlite => write!(f, "SQLite"),
+            SqlDialect::Mssql => write!(f, "SQL Server"),
+        }
+    }
+}
+
+/// Possible handling of lines that cannot be split into fields
+#[derive(Debug, Clone, ValueEnum)]
+pub enum OnError {
+    /// Leave the line out of the table
+    Skip,
+    /// Keep the raw text of the line as a single field
+    KeepRaw,
+    /// Stop with an error
+    Fail,
+}
+
+impl fmt::Display for OnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OnError::Skip => write!(f, "Skip"),
+            OnError::KeepRaw => write!(f, "Keep raw"),
+            OnError::Fail => write!(f, "Fail"),
+        }
+    }
+}
+
+/// Possible handling of rows with a different number of fields to the first row
+#[derive(Debug, Clone, ValueEnum)]
+pub enum Ragged {
+    /// Pad short rows with empty fields to the width of the widest row
+    Pad,
+    /// Truncate long rows and pad short rows to the width of the first row
+    Truncate,
+    /// Treat the row as an error, handled by --on-error
+    Error,
+    /// Join surplus trailing fields into the last field and pad short rows
+    MergeTail,
+}
+
+impl fmt::Display for Ragged {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Ragged::Pad => write!(f, "Pad"),
+            Ragged::Truncate => write!(f, "Truncate"),
+            Ragged::Error => write!(f, "Error"),
+            Ragged::MergeTail => write!(f, "Merge tail"),
+        }
+    }
+}
+
+/// Possible CSS themes of a standalone HTML document
+#[derive(Debug, Clone, ValueEnum)]
+pub enum HtmlTheme {
+    /// No embedded CSS
+    None,
+    /// Dark text on a light background
+    Light,
+    /// Light text on a dark background
+    Dark,
+}
+
+impl fmt::Display for HtmlTheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HtmlTheme::None => write!(f, "None"),
+            HtmlTheme::Light => write!(f, "Light"),
+            HtmlTheme::Dark => write!(f, "Dark"),
+        }
+    }
+}
+
+/// Possible SQL INSERT conflict handling
+#[derive(Debug, Clone, ValueEnum)]
+pub enum SqlOnConflict {
+    /// Plain INSERT, conflicting rows raise an error
+    Error,
+    /// Skip rows that conflict with existing rows
+    Ignore,
+    /// Replace existing rows with conflicting rows
+    Replace,
+}
+
+impl fmt::Display for SqlOnConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SqlOnConflict::Error => write!(f, "Error"),
+            SqlOnConflict::Ignore => write!(f, "Ignore"),
+            SqlOnConflict::Replace => write!(f, "Replace"),
+        }
+    }
+}
+
 /// Possible text field delimiters
 #[derive(Debug, Clone, ValueEnum)]
 pub enum Delimiter {
@@ -112,3 +447,55 @@ impl Delimiter {
         }
     }
 }
+
+/// Possible quote characters
+#[derive(Debug, Clone, ValueEnum)]
+pub enum QuoteChar {
+    /// Double quote (")
+    Double,
+    /// Single quote (')
+    Single,
+    /// Backtick (`)
+    Backtick,
+}
+
+impl fmt::Display for QuoteChar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QuoteChar::Double => write!(f, "Double quote"),
+            QuoteChar::Single => write!(f, "Single quote"),
+            QuoteChar::Backtick => write!(f, "Backtick"),
+        }
+    }
+}
+
+impl QuoteChar {
+    pub fn as_char(&self) -> char {
+        match self {
+            QuoteChar::Double => DOUBLE_QUOTE,
+            QuoteChar::Single => SINGLE_QUOTE,
+            QuoteChar::Backtick => BACKTICK,
+        }
+    }
+}
+
+/// Possible escapes of quote characters within quoted fields
+#[derive(Debug, Clone, ValueEnum)]
+pub enum QuoteEscape {
+    /// No escapes, a quote character always ends the quoted field
+    None,
+    /// A doubled quote character ("") is a literal quote
+    Doubled,
+    /// A backslash escapes a quote character or backslash (\")
+    Backslash,
+}
+
+impl fmt::Display for QuoteEscape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QuoteEscape::None => write!(f, "None"),
+            QuoteEscape::Doubled => write!(f, "Doubled"),
+            QuoteEscape::Backslash => write!(f, "Backslash"),
+        }
+    }
+}
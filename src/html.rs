@@ -0,0 +1,811 @@
+//!
+//! HTML table parsing and generation
+//!
+use crate::cli_args::HtmlTheme;
+use crate::table::default_column_name;
+use crate::types::{ColumnType, ValueType};
+use build_html::{
+    escape_html, Html, HtmlContainer, HtmlPage, Table, TableCell, TableCellType, TableRow,
+};
+use thiserror::Error;
+
+/// Maximum number of rows or columns a single cell can span
+const MAX_SPAN: usize = 1000;
+
+/// Elements whose contents are not text, and are skipped
+const RAW_TEXT_ELEMENTS: [&str; 2] = ["script", "style"];
+
+/// Elements that start a new line of text within a cell when they end
+const LINE_BREAK_ELEMENTS: [&str; 4] = ["p", "div", "li", "tr"];
+
+/// Title of a standalone HTML document without a caption
+const DEFAULT_TITLE: &str = "Table";
+
+/// CSS shared by all themes of a standalone HTML document
+const BASE_CSS: &str = "\
+body { font-family: system-ui, sans-serif; margin: 2rem; }
+table { border-collapse: collapse; }
+caption { font-weight: bold; padding: 0.5rem; }
+th, td { padding: 0.25rem 0.75rem; text-align: left; vertical-align: top; }";
+
+/// CSS of the light theme
+const LIGHT_CSS: &str = "\
+body { background: #fff; color: #222; }
+th, td { border: 1px solid #ccc; }
+thead th { background: #f0f0f0; }";
+
+/// CSS of the dark theme
+const DARK_CSS: &str = "\
+body { background: #1e1e1e; color: #ddd; }
+th, td { border: 1px solid #444; }
+thead th { background: #2d2d2d; }";
+
+/// CSS of an interactive HTML document, for sticky headers, sortable columns, and zebra stripes
+const INTERACTIVE_CSS: &str = "\
+#ttt-filter { margin-bottom: 0.75rem; padding: 0.25rem 0.5rem; min-width: 16rem; }
+thead th { position: sticky; top: 0; background: Canvas; cursor: pointer; user-select: none; }
+thead th[aria-sort=ascending]::after { content: ' \\25B2'; }
+thead th[aria-sort=descending]::after { content: ' \\25BC'; }
+tbody tr.ttt-stripe { background: rgba(128, 128, 128, 0.12); }";
+
+/// Script of an interactive HTML document, sorting rows when a header is clicked and filtering
+/// rows by the text in the filter box
+const INTERACTIVE_JS: &str = r#"
+document.addEventListener('DOMContentLoaded', function () {
+  var table = document.querySelector('table');
+  var tbody = table.tBodies[0];
+  var filter = document.getElementById('ttt-filter');
+  function stripe() {
+    var visible = 0;
+    Array.prototype.forEach.call(tbody.rows, function (row) {
+      if (row.hidden) return;
+      row.classList.toggle('ttt-stripe', visible % 2 === 1);
+      visible += 1;
+    });
+  }
+  function key(row, index, numeric) {
+    var cell = row.cells[index];
+    var text = cell ? cell.textContent.trim() : '';
+    if (!numeric) return text.toLowerCase();
+    var number = parseFloat(text);
+    return isNaN(number) ? -Infinity : number;
+  }
+  Array.prototype.forEach.call(table.tHead.rows[0].cells, function (th, index) {
+    th.addEventListener('click', function () {
+      var ascending = th.getAttribute('aria-sort') !== 'ascending';
+      var numeric = th.dataset.type === 'number';
+      Array.prototype.forEach.call(th.parentNode.cells, function (other) {
+        other.removeAttribute('aria-sort');
+      });
+      th.setAttribute('aria-sort', ascending ? 'ascending' : 'descending');
+      var rows = Array.prototype.slice.call(tbody.rows);
+      rows.sort(function (a, b) {
+        var x = key(a, index, numeric);
+        var y = key(b, index, numeric);
+        var order = x < y ? -1 : x > y ? 1 : 0;
+        return ascending ? order : -order;
+      });
+      rows.forEach(function (row) { tbody.appendChild(row); });
+      stripe();
+    });
+  });
+  filter.addEventListener('input', function () {
+    var query = filter.value.toLowerCase();
+    Array.prototype.forEach.call(tbody.rows, function (row) {
+      row.hidden = row.textContent.toLowerCase().indexOf(query) === -1;
+    });
+    stripe();
+  });
+  stripe();
+});
+"#;
+
+#[derive(Error, Debug)]
+pub enum HtmlError {
+    #[error("No tables found in HTML")]
+    NoTables,
+    #[error("No table found with index {0}")]
+    IndexNotFound(usize),
+    #[error("No table found with id {0:?}")]
+    IdNotFound(String),
+}
+
+///
+/// Ways of choosing which table to read from an HTML document.
+///
+#[derive(Debug, Clone)]
+pub enum HtmlTableSelector {
+    /// 0-based index of the table in document order
+    Index(usize),
+    /// Value of the table's id attribute
+    Id(String),
+}
+
+///
+/// Part of an HTML document.
+///
+#[derive(Debug, PartialEq)]
+enum Token {
+    StartTag {
+        name: String,
+        attributes: Vec<(String, String)>,
+    },
+    EndTag {
+        name: String,
+    },
+    Text(String),
+}
+
+///
+/// Decode character references such as `&amp;`, `&#39;` and `&#x27;`. Unknown references are
+/// kept as they are.
+///
+pub fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 32)
+            .map(|end| &rest[1..end + 1]);
+        let c = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => match entity.strip_prefix('#') {
+                Some(hex) if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16)
+                    .ok()
+                    .and_then(char::from_u32),
+                Some(dec) => dec.parse::<u32>().ok().and_then(char::from_u32),
+                None => None,
+            },
+        });
+        match (entity, c) {
+            (Some(entity), Some(c)) => {
+                decoded.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+///
+/// Parse the attributes of a start tag, up to but not including the closing `>`. Returns the
+/// attributes and the number of bytes read.
+///
+fn parse_attributes(tag: &str) -> (Vec<(String, String)>, usize) {
+    let mut attributes = Vec::new();
+    let mut i = 0;
+    let bytes = tag.as_bytes();
+    loop {
+        while i < bytes.len() && (bytes[i].is_ascii_whitespace() || bytes[i] == b'/') {
+            i += 1;
+        }
+        if i >= bytes.len() || bytes[i] == b'>' {
+            return (attributes, i);
+        }
+        let start = i;
+        while i < bytes.len() && !matches!(bytes[i], b'=' | b'>' | b'/') {
+            if bytes[i].is_ascii_whitespace() {
+                break;
+            }
+            i += 1;
+        }
+        let name = tag[start..i].to_ascii_lowercase();
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let mut value = String::new();
+        if i < bytes.len() && bytes[i] == b'=' {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            let (start, end, next) = match bytes.get(i) {
+                Some(&quote) if quote == b'"' || quote == b'\'' => {
+                    let end = tag[i + 1..]
+                        .find(quote as char)
+                        .map_or(tag.len(), |end| i + 1 + end);
+                    (i + 1, end, (end + 1).min(tag.len()))
+                }
+                _ => {
+                    let end = tag[i..]
+                        .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                        .map_or(tag.len(), |end| i + end);
+                    (i, end, end)
+                }
+            };
+            value = decode_entities(&tag[start..end]);
+            i = next;
+        }
+        if !name.is_empty() {
+            attributes.push((name, value));
+        }
+    }
+}
+
+///
+/// Split an HTML document into start tags, end tags, and text. Comments, doctypes, and the
+/// contents of scripts and styles are skipped.
+///
+fn tokenize(document: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = document;
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            tokens.push(Token::Text(decode_entities(rest)));
+            break;
+        };
+        let after = &rest[start + 1..];
+        let (token, consumed) = if after.starts_with("!--") {
+            let end = after.find("-->").map_or(after.len(), |end| end + 3);
+            (None, end)
+        } else if after.starts_with(['!', '?']) {
+            let end = after.find('>').map_or(after.len(), |end| end + 1);
+            (None, end)
+        } else if let Some(tag) = after.strip_prefix('/') {
+            let end = tag.find('>').map_or(tag.len(), |end| end + 1);
+            let name = tag[..end].trim_end_matches('>').trim().to_ascii_lowercase();
+            (Some(Token::EndTag { name }), end + 1)
+        } else if after.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let name_end = after
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+                .unwrap_or(after.len());
+            let name = after[..name_end].to_ascii_lowercase();
+            let (attributes, len) = parse_attributes(&after[name_end..]);
+            let mut end = (name_end + len + 1).min(after.len());
+            if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                let close = format!("</{}", name);
+                end = after[end..]
+                    .to_ascii_lowercase()
+                    .find(&close)
+                    .map_or(after.len(), |i| end + i);
+            }
+            (Some(Token::StartTag { name, attributes }), end)
+        } else {
+            tokens.push(Token::Text(decode_entities(&rest[..start + 1])));
+            rest = after;
+            continue;
+        };
+
+        if start > 0 {
+            tokens.push(Token::Text(decode_entities(&rest[..start])));
+        }
+        tokens.extend(token);
+        rest = &after[consumed.min(after.len())..];
+    }
+    tokens
+}
+
+///
+/// A cell of a table before spans are expanded.
+///
+#[derive(Debug)]
+struct Cell {
+    text: String,
+    colspan: usize,
+    rowspan: usize,
+}
+
+///
+/// A table being parsed, and the row and cell currently open.
+///
+#[derive(Debug, Default)]
+struct ParsedTable {
+    id: Option<String>,
+    rows: Vec<Vec<Cell>>,
+    /// Whether each row is in a `<thead>` or has only `<th>` cells
+    header_rows: Vec<bool>,
+    has_header_cells: bool,
+    in_thead: bool,
+    row: Option<(Vec<Cell>, bool)>,
+    cell: Option<(Cell, bool)>,
+}
+
+impl ParsedTable {
+    fn close_cell(&mut self) {
+        if let Some((mut cell, is_header)) = self.cell.take() {
+            cell.text = cell
+                .text
+                .lines()
+                .map(|line| line.split_whitespace().collect::<Vec<&str>>().join(" "))
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<String>>()
+                .join("\n");
+            let (cells, all_header) = self.row.get_or_insert_with(|| (vec![], true));
+            *all_header &= is_header;
+            cells.push(cell);
+        }
+    }
+
+    fn close_row(&mut self) {
+        self.close_cell();
+        if let Some((cells, is_header)) = self.row.take().filter(|(cells, _)| !cells.is_empty()) {
+            self.rows.push(cells);
+            self.header_rows.push(is_header);
+        }
+    }
+
+    ///
+    /// Expand cells that span several rows or columns into a rectangular grid, repeating the
+    /// text of the cell in each position it covers.
+    ///
+    fn to_grid(&self) -> Vec<Vec<String>> {
+        let mut grid: Vec<Vec<Option<String>>> = vec![vec![]; self.rows.len()];
+        for (r, cells) in self.rows.iter().enumerate() {
+            let mut c = 0;
+            for cell in cells {
+                while grid[r].get(c).is_some_and(|slot| slot.is_some()) {
+                    c += 1;
+                }
+                let last_row = (r + cell.rowspan).min(self.rows.len());
+                for row in grid[r..last_row].iter_mut() {
+                    if row.len() < c + cell.colspan {
+                        row.resize(c + cell.colspan, None);
+                    }
+                    for slot in row[c..c + cell.colspan].iter_mut() {
+                        *slot = Some(cell.text.clone());
+                    }
+                }
+                c += cell.colspan;
+            }
+        }
+
+        let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+        grid.into_iter()
+            .map(|row| {
+                let mut row = row
+                    .into_iter()
+                    .map(Option::unwrap_or_default)
+                    .collect::<Vec<String>>();
+                row.resize(width, String::new());
+                row
+            })
+            .collect()
+    }
+}
+
+///
+/// Get a row or column span from a cell's attributes, clamped to a sensible range.
+///
+fn span(attributes: &[(String, String)], name: &str) -> usize {
+    attributes
+        .iter()
+        .find(|(key, _)| key == name)
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(1)
+        .clamp(1, MAX_SPAN)
+}
+
+///
+/// Parse all tables in an HTML document, in the order they start. Nested tables are separate
+/// tables, and their text is not part of the enclosing cell.
+///
+fn parse_tables(document: &str) -> Vec<ParsedTable> {
+    let mut tables: Vec<(usize, ParsedTable)> = Vec::new();
+    let mut open: Vec<(usize, ParsedTable)> = Vec::new();
+    let mut count = 0;
+
+    for token in tokenize(document) {
+        match token {
+            Token::StartTag { name, attributes } if name == "table" => {
+                let id = attributes
+                    .iter()
+                    .find(|(key, _)| key == "id")
+                    .map(|(_, value)| value.clone());
+                open.push((
+                    count,
+                    ParsedTable {
+                        id,
+                        ..ParsedTable::default()
+                    },
+                ));
+                count += 1;
+            }
+            Token::EndTag { name } if name == "table" => {
+                if let Some((index, mut table)) = open.pop() {
+                    table.close_row();
+                    tables.push((index, table));
+                }
+            }
+            token => {
+                let Some((_, table)) = open.last_mut() else {
+                    continue;
+                };
+                match token {
+                    Token::StartTag { name, attributes } => match name.as_str() {
+                        "thead" => {
+                            table.close_row();
+                            table.in_thead = true;
+                        }
+                        "tbody" | "tfoot" => {
+                            table.close_row();
+                            table.in_thead = false;
+                        }
+                        "tr" => {
+                            table.close_row();
+                            table.row = Some((vec![], true));
+                        }
+                        "td" | "th" => {
+                            table.close_cell();
+                            table.has_header_cells |= name == "th" || table.in_thead;
+                            let cell = Cell {
+                                text: String::new(),
+                                colspan: span(&attributes, "colspan"),
+                                rowspan: span(&attributes, "rowspan"),
+                            };
+                            table.cell = Some((cell, name == "th" || table.in_thead));
+                        }
+                        "br" => {
+                            if let Some((cell, _)) = table.cell.as_mut() {
+                                cell.text.push('\n');
+                            }
+                        }
+                        _ => {}
+                    },
+                    Token::EndTag { name } => match name.as_str() {
+                        "thead" => {
+                            table.close_row();
+                            table.in_thead = false;
+                        }
+                        "tr" => table.close_row(),
+                        "td" | "th" => table.close_cell(),
+                        name if LINE_BREAK_ELEMENTS.contains(&name) => {
+                            if let Some((cell, _)) = table.cell.as_mut() {
+                                cell.text.push('\n');
+                            }
+                        }
+                        _ => {}
+                    },
+                    Token::Text(text) => {
+                        if let Some((cell, _)) = table.cell.as_mut() {
+                            cell.text.push_str(&text);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    // Tables that are never closed end with the document
+    while let Some((index, mut table)) = open.pop() {
+        table.close_row();
+        tables.push((index, table));
+    }
+
+    tables.sort_by_key(|(index, _)| *index);
+    tables.into_iter().map(|(_, table)| table).collect()
+}
+
+///
+/// Read a table from an HTML document as rows of text. Also returns whether the first row is a
+/// header, if the table marks its header with `<thead>` or `<th>`.
+///
+pub fn read_html_table(
+    document: &str,
+    selector: &HtmlTableSelector,
+) -> Result<(Vec<Vec<String>>, Option<bool>), HtmlError> {
+    let mut tables = parse_tables(document);
+    if tables.is_empty() {
+        return Err(HtmlError::NoTables);
+    }
+    let index = match selector {
+        HtmlTableSelector::Index(index) if *index < tables.len() => *index,
+        HtmlTableSelector::Index(index) => return Err(HtmlError::IndexNotFound(*index)),
+        HtmlTableSelector::Id(id) => tables
+            .iter()
+            .position(|table| table.id.as_ref() == Some(id))
+            .ok_or_else(|| HtmlError::IdNotFound(id.clone()))?,
+    };
+    let table = tables.swap_remove(index);
+    let has_header = table
+        .has_header_cells
+        .then(|| table.header_rows.first().copied().unwrap_or(false));
+
+    Ok((table.to_grid(), has_header))
+}
+
+///
+/// Render a value for an HTML cell, escaping markup and keeping line breaks.
+///
+fn to_html_value(value: &str) -> String {
+    escape_html(value)
+        .replace("\r\n", "<br>")
+        .replace(['\n', '\r'], "<br>")
+}
+
+///
+/// HTML table
+///
+#[derive(Debug)]
+pub struct HtmlTable {
+    contents: Vec<Vec<String>>,
+    has_header: bool,
+    standalone: bool,
+    interactive: bool,
+    theme: HtmlTheme,
+    caption: Option<String>,
+    id: Option<String>,
+    class: Option<String>,
+}
+
+impl HtmlTable {
+    ///
+    /// Create a new HTML table from a 2D vector of data.
+    ///
+    pub fn new(contents: Vec<Vec<String>>) -> Self {
+        HtmlTable {
+            contents,
+            has_header: false,
+            standalone: false,
+            interactive: false,
+            theme: HtmlTheme::None,
+            caption: None,
+            id: None,
+            class: None,
+        }
+    }
+
+    ///
+    /// Set to true to use the first row of data as the header.
+    ///
+    pub fn has_header(&mut self, has_header: bool) -> &mut Self {
+        self.has_header = has_header;
+        self
+    }
+
+    ///
+    /// Set to true to output a complete HTML document rather than only the table element.
+    ///
+    pub fn standalone(&mut self, standalone: bool) -> &mut Self {
+        self.standalone = standalone;
+        self
+    }
+
+    ///
+    /// Set to true to output a standalone document with sortable columns, a filter box, sticky
+    /// headers, and zebra stripes. Tables without a header are given numbered column names.
+    ///
+    pub fn interactive(&mut self, interactive: bool) -> &mut Self {
+        self.interactive = interactive;
+        self
+    }
+
+    ///
+    /// Set the CSS theme embedded in a standalone document.
+    ///
+    pub fn theme(&mut self, theme: HtmlTheme) -> &mut Self {
+        self.theme = theme;
+        self
+    }
+
+    ///
+    /// Set the caption of the table, which is also the title of a standalone document.
+    ///
+    pub fn caption(&mut self, caption: Option<String>) -> &mut Self {
+        self.caption = caption;
+        self
+    }
+
+    ///
+    /// Set the id attribute of the table element.
+    ///
+    pub fn id(&mut self, id: Option<String>) -> &mut Self {
+        self.id = id;
+        self
+    }
+
+    ///
+    /// Set the class attribute of the table element.
+    ///
+    pub fn class(&mut self, class: Option<String>) -> &mut Self {
+        self.class = class;
+        self
+    }
+
+    ///
+    /// Build the table element.
+    ///
+    fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        let attributes = [("id", &self.id), ("class", &self.class)]
+            .into_iter()
+            .filter_map(|(name, value)| {
+                value
+                    .as_ref()
+                    .map(|value| (name.to_string(), escape_html(value)))
+            })
+            .collect::<Vec<(String, String)>>();
+        if !attributes.is_empty() {
+            table = table.with_attributes(attributes);
+        }
+        if let Some(caption) = &self.caption {
+            table.add_caption(escape_html(caption));
+        }
+
+        let mut rows = self.contents.iter();
+        let num_columns = self.contents.iter().map(|row| row.len()).max().unwrap_or(0);
+        let header = match (self.has_header, self.interactive) {
+            (true, _) => rows.next().cloned(),
+            (false, true) => Some((0..num_columns).map(default_column_name).collect()),
+            (false, false) => None,
+        };
+        match header {
+            Some(header) if self.interactive => {
+                let body = rows.as_slice();
+                let row = header
+                    .iter()
+                    .enumerate()
+                    .fold(TableRow::new(), |row, (i, value)| {
+                        let column_type = ColumnType::infer(
+                            body.iter()
+                                .map(|row| row.get(i).map_or("", |value| value.as_str())),
+                        );
+                        let data_type = match column_type.value_type {
+                            ValueType::Integer | ValueType::Real => "number",
+                            _ => "text",
+                        };
+                        row.with_cell(
+                            TableCell::new(TableCellType::Header)
+                                .with_attributes([("data-type", data_type)])
+                                .with_raw(to_html_value(value)),
+                        )
+                    });
+                table.add_custom_header_row(row);
+            }
+            Some(header) => table.add_header_row(header.iter().map(|value| to_html_value(value))),
+            None => {}
+        }
+        for row in rows {
+            table.add_body_row(row.iter().map(|value| to_html_value(value)));
+        }
+        table
+    }
+
+    ///
+    /// Output the table as an HTML string, either as a table element or a complete document.
+    ///
+    pub fn to_html(&self) -> String {
+        let table = self.to_table();
+        if !self.standalone && !self.interactive {
+            return format!("{}\n", table.to_html_string());
+        }
+
+        let title = self.caption.as_deref().unwrap_or(DEFAULT_TITLE);
+        let mut page = HtmlPage::new()
+            .with_meta([("charset", "utf-8")])
+            .with_title(escape_html(title));
+        // Added before the theme, so the theme can override the opaque sticky header background
+        if self.interactive {
+            page.add_style(INTERACTIVE_CSS);
+        }
+        match self.theme {
+            HtmlTheme::None => {}
+            HtmlTheme::Light => page.add_style(format!("{}\n{}", BASE_CSS, LIGHT_CSS)),
+            HtmlTheme::Dark => page.add_style(format!("{}\n{}", BASE_CSS, DARK_CSS)),
+        }
+        if self.interactive {
+            page.add_script_literal(INTERACTIVE_JS);
+            page.add_raw(
+                r#"<input id="ttt-filter" type="search" placeholder="Filter rows" aria-label="Filter rows">"#,
+            );
+        }
+        format!("{}\n", page.with_table(table).to_html_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENT: &str = r#"<!DOCTYPE html>
+<html><head><style>td { color: red; }</style></head>
+<body>
+<!-- <table><tr><td>commented out</td></tr></table> -->
+<table class="summary"><tr><td>first</td></tr></table>
+<table id='report'>
+  <caption>Report</caption>
+  <thead><tr><th>Region<th colspan=2>Sales &amp; returns</tr></thead>
+  <tbody>
+    <tr><td rowspan="2"><b>North</b></td><td>1</td><td>2</td></tr>
+    <tr><td>3<br>4</td><td><table><tr><td>nested</td></tr></table>5</td></tr>
+  </tbody>
+</table>
+</body></html>
+"#;
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("a &amp; b &lt;&#39;&#x41;&gt; &c"),
+            "a & b <'A> &c"
+        );
+    }
+
+    #[test]
+    fn test_read_html_table() {
+        let (rows, has_header) =
+            read_html_table(DOCUMENT, &HtmlTableSelector::Id("report".to_string())).unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                vec!["Region", "Sales & returns", "Sales & returns"],
+                vec!["North", "1", "2"],
+                vec!["North", "3\n4", "5"],
+            ]
+        );
+        assert_eq!(has_header, Some(true));
+
+        let (rows, has_header) = read_html_table(DOCUMENT, &HtmlTableSelector::Index(0)).unwrap();
+        assert_eq!(rows, vec![vec!["first"]]);
+        assert_eq!(has_header, None);
+
+        assert_eq!(
+            read_html_table(DOCUMENT, &HtmlTableSelector::Index(2))
+                .unwrap()
+                .0,
+            vec![vec!["nested"]]
+        );
+        assert!(read_html_table(DOCUMENT, &HtmlTableSelector::Index(3)).is_err());
+    }
+
+    #[test]
+    fn test_to_html() {
+        let contents = vec![
+            vec!["name".to_string(), "note".to_string()],
+            vec!["<b>".to_string(), "a & b\nc".to_string()],
+        ];
+
+        let html = HtmlTable::new(contents.clone())
+            .has_header(true)
+            .id(Some("t1".to_string()))
+            .to_html();
+        assert_eq!(
+            html,
+            "<table id=\"t1\"><thead><tr><th>name</th><th>note</th></tr></thead>\
+             <tbody><tr><td>&lt;b&gt;</td><td>a &amp; b<br>c</td></tr></tbody></table>\n"
+        );
+
+        let page = HtmlTable::new(contents)
+            .standalone(true)
+            .theme(HtmlTheme::Dark)
+            .caption(Some("Q&A".to_string()))
+            .to_html();
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<title>Q&amp;A</title>"));
+        assert!(page.contains("<caption>Q&amp;A</caption>"));
+        assert!(page.contains(DARK_CSS));
+        assert!(page.contains("<tbody><tr><td>name</td>"));
+    }
+
+    #[test]
+    fn test_to_html_interactive() {
+        let contents = vec![
+            vec!["a".to_string(), "10".to_string()],
+            vec!["b".to_string(), "9.5".to_string()],
+        ];
+
+        let page = HtmlTable::new(contents).interactive(true).to_html();
+
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains(
+            "<thead><tr><th data-type=\"text\">col_1</th><th data-type=\"number\">col_2</th>\
+             </tr></thead>"
+        ));
+        assert!(page.contains("<script>"));
+        assert!(page.contains(r#"<input id="ttt-filter""#));
+        assert!(page.contains("position: sticky; top: 0; background: Canvas;"));
+        assert!(!page.contains("http"));
+    }
+}
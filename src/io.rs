@@ -1,12 +1,55 @@
-use crate::cli_args::{CliArgs, TableOutputFmt};
-use crate::markdown::MarkdownTable;
-use crate::sqlddl::SqlDdlTable;
-use crate::table::{Table, TableBuilder};
-use build_html::{Html, Table as HtmlTable};
+use crate::cli_args::{CliArgs, OnError, TableInputFmt, TableOutputFmt};
+use crate::html::{HtmlTable, HtmlTableSelector};
+use crate::json::{JsonLines, JsonTable};
+use crate::lines::Quoting;
+use crate::markdown::{format_markdown_tables, MarkdownTable};
+use crate::sqlddl::{sanitize_identifier, SqlDdlTable, DEFAULT_TABLE_NAME};
+use crate::table::{ColumnBoundaries, LineFilter, Rejected, Table, TableBuilder};
+use crate::toml::TomlTable;
+use crate::types::detect_header;
+use crate::yaml::YamlTable;
 use csv::WriterBuilder;
+use log::{info, warn};
 use std::error::Error;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::prelude::*;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+///
+/// Get the path of a file, or None if stdin / stdout should be used instead.
+///
+pub fn file_path(path: &Option<PathBuf>) -> Option<&Path> {
+    path.as_deref().filter(|p| p.as_os_str() != "-")
+}
+
+///
+/// Open the output file for writing, or stdout if there is no output file.
+///
+fn create_output(path: &Option<PathBuf>) -> Result<Box<dyn Write>, Box<dyn Error>> {
+    match file_path(path) {
+        Some(path) => Ok(Box::new(BufWriter::new(File::create(path)?))),
+        None => Ok(Box::new(BufWriter::new(io::stdout().lock()))),
+    }
+}
+
+///
+/// Write records that could not be split to a CSV file, with their line numbers and errors.
+///
+fn write_rejects(path: &Path, rejected: &[Rejected]) -> Result<(), Box<dyn Error>> {
+    let mut wtr = WriterBuilder::new().from_path(path)?;
+    wtr.write_record(["line", "column", "error", "text"])?;
+    for rejected in rejected {
+        wtr.write_record([
+            rejected.error.line_number.to_string(),
+            rejected.error.column.to_string(),
+            rejected.error.source.to_string(),
+            rejected.record.clone(),
+        ])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
 
 ///
 /// Primary entrypoint for reading a file, converting to a Table of Lines, parsing, and then
@@ -14,26 +57,201 @@ use std::io::prelude::*;
 ///
 pub fn read(args: &CliArgs) -> Result<Table, Box<dyn Error>> {
     let delimiters = args.delimiters.iter().map(|d| d.as_char()).collect();
-    let table = TableBuilder::new()
+    let fixed_width = match args.input_format {
+        TableInputFmt::Delimited
+        | TableInputFmt::Boxed
+        | TableInputFmt::Markdown
+        | TableInputFmt::Html => None,
+        TableInputFmt::FixedWidth if args.header_columns => Some(ColumnBoundaries::Header),
+        TableInputFmt::FixedWidth if args.column_offsets.is_empty() => Some(ColumnBoundaries::Auto),
+        TableInputFmt::FixedWidth => Some(ColumnBoundaries::Offsets(args.column_offsets.clone())),
+    };
+    let html_table = match (&args.input_format, &args.table_id) {
+        (TableInputFmt::Html, Some(id)) => Some(HtmlTableSelector::Id(id.clone())),
+        (TableInputFmt::Html, None) => Some(HtmlTableSelector::Index(args.table_index)),
+        _ => None,
+    };
+    let mut table = TableBuilder::new();
+    table
         .quoted_fields(args.quoted_fields)
+        .quoting(Quoting {
+            quotes: args.quote_chars.iter().map(|q| q.as_char()).collect(),
+            escape: args.quote_escape.clone(),
+            keep_quotes: args.keep_quotes,
+        })
         .contiguous_delimiters(args.contiguous_delimiters)
         .delimiters(delimiters)
-        .from_path(&args.input)?;
+        .delimiter_strs(args.delimiter_str.clone())
+        .delimiter_regex(args.delimiter_regex.clone())
+        .fixed_width(fixed_width)
+        .boxed(matches!(args.input_format, TableInputFmt::Boxed))
+        .markdown(matches!(args.input_format, TableInputFmt::Markdown))
+        .html_table(html_table)
+        .auto(args.auto)
+        .sample_lines(args.sample_lines)
+        .line_filter(LineFilter {
+            skip_lines: args.skip_lines,
+            skip_footer: args.skip_footer,
+            comment_prefix: args.comment_prefix.clone(),
+            skip_blank_lines: args.skip_blank_lines,
+        })
+        .streaming(matches!(args.format, TableOutputFmt::Jsonl));
+    let table = match file_path(&args.input) {
+        Some(path) => table.from_path(path)?,
+        None => table.from_reader(io::stdin().lock())?,
+    };
 
     Ok(table)
 }
 
 ///
-/// Write outputs to file in the specified format.
+/// Reformat the tables in markdown files in place, or only check them. Returns whether any file
+/// was, or would be, changed.
+///
+pub fn format_markdown_files(files: &[PathBuf], check: bool) -> Result<bool, Box<dyn Error>> {
+    let mut changed = false;
+    for path in files {
+        let document = fs::read_to_string(path)?;
+        let formatted = format_markdown_tables(&document)?;
+        if formatted == document {
+            info!("Unchanged {}", path.display());
+            continue;
+        }
+        changed = true;
+        if check {
+            warn!("Would reformat {}", path.display());
+        } else {
+            fs::write(path, formatted)?;
+            info!("Reformatted {}", path.display());
+        }
+    }
+    Ok(changed)
+}
+
+///
+/// Get the table name for the output, from the arguments or the name of the input file.
+///
+fn table_name(args: &CliArgs) -> String {
+    match (&args.table_name, file_path(&args.input)) {
+        (Some(table_name), _) => table_name.clone(),
+        (None, Some(path)) => {
+            sanitize_identifier(&path.file_stem().unwrap_or_default().to_string_lossy())
+        }
+        (None, None) => DEFAULT_TABLE_NAME.to_string(),
+    }
+}
+
+///
+/// Write outputs to file, or stdout, in the specified format.
 ///
 pub fn write(args: &CliArgs, table: Table) -> Result<(), Box<dyn Error>> {
-    let contents = table.split()?;
+    let rejected = match args.format {
+        TableOutputFmt::Jsonl => write_json_lines(args, table)?,
+        _ => write_table(args, &table)?,
+    };
+
+    if let Some(path) = &args.rejects {
+        write_rejects(path, &rejected)?;
+    }
+    if !rejected.is_empty() {
+        let action = match args.on_error {
+            OnError::KeepRaw => "kept as raw text",
+            _ => "skipped",
+        };
+        let num_lines = rejected.iter().map(Rejected::num_lines).sum::<usize>();
+        warn!(
+            "{} malformed record(s) ({} line(s)) {}",
+            rejected.len(),
+            num_lines,
+            action
+        );
+    }
+    Ok(())
+}
+
+///
+/// Whether the first row is a header, from the flags, the layout of the input, or detected from
+/// the rows.
+///
+fn resolve_has_header(args: &CliArgs, layout_header: Option<bool>, rows: &[Vec<String>]) -> bool {
+    match (args.has_header, args.no_header, layout_header) {
+        (true, _, _) => true,
+        (_, true, _) => false,
+        (_, _, Some(separated)) => {
+            info!("Detected header row from separator line: {}", separated);
+            separated
+        }
+        _ => {
+            let detected = detect_header(rows);
+            info!("Detected header row: {}", detected);
+            detected
+        }
+    }
+}
+
+///
+/// Write JSON Lines as each row is read and split, flushing each line. Unless the header is known
+/// up front, the first rows are held back until there are enough to detect the header.
+///
+fn write_json_lines(args: &CliArgs, table: Table) -> Result<Vec<Rejected>, Box<dyn Error>> {
+    let mut writer = create_output(&args.output)?;
+    let layout_header = match (args.has_header, args.no_header) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => table.has_header(),
+    };
+    let mut pending = Vec::new();
+    let mut json_lines: Option<JsonLines> = None;
+
+    let start = |pending: &mut Vec<Vec<String>>, writer: &mut dyn Write| {
+        let mut lines = JsonLines::new();
+        lines.infer_types(args.infer_types);
+        if resolve_has_header(args, layout_header, pending) && !pending.is_empty() {
+            lines.header(Some(pending.remove(0)));
+        }
+        for row in pending.drain(..) {
+            writeln!(writer, "{}", lines.to_json_line(&row))?;
+        }
+        writer.flush()?;
+        Ok::<JsonLines, io::Error>(lines)
+    };
+
+    let rejected = table.stream_each(&args.ragged, &args.on_error, |row| {
+        match &json_lines {
+            Some(lines) => {
+                writeln!(writer, "{}", lines.to_json_line(&row))?;
+                writer.flush()?;
+            }
+            None => {
+                pending.push(row);
+                if layout_header.is_some() || pending.len() >= args.sample_lines {
+                    json_lines = Some(start(&mut pending, &mut writer)?);
+                }
+            }
+        }
+        Ok::<(), Box<dyn Error>>(())
+    })?;
+    if json_lines.is_none() {
+        start(&mut pending, &mut writer)?;
+    }
+    writer.flush()?;
+    Ok(rejected)
+}
+
+///
+/// Write all rows of the table at once, for formats that need every row before writing.
+///
+fn write_table(args: &CliArgs, table: &Table) -> Result<Vec<Rejected>, Box<dyn Error>> {
+    let split = table.split(&args.ragged, &args.on_error)?;
+    let alignments = table.alignments();
+    let contents = split.rows;
+    let has_header = resolve_has_header(args, split.has_header, &contents);
+    let mut writer = create_output(&args.output)?;
     match args.format {
         TableOutputFmt::Csv => {
             let mut wtr = WriterBuilder::new()
-                .flexible(true)
                 .has_headers(false)
-                .from_path(&args.output)?;
+                .from_writer(&mut writer);
             for result in contents.into_iter() {
                 wtr.write_record(&result)?;
             }
@@ -41,24 +259,71 @@ pub fn write(args: &CliArgs, table: Table) -> Result<(), Box<dyn Error>> {
         }
         TableOutputFmt::Md => {
             let md_table = MarkdownTable::new(contents)
-                .has_header(args.has_header)
+                .has_header(has_header)
+                .alignments(alignments)
                 .to_markdown()?;
-            let mut file = File::create(&args.output)?;
-            file.write_all(md_table.as_bytes())?;
+            writer.write_all(md_table.as_bytes())?;
+        }
+        TableOutputFmt::Html | TableOutputFmt::HtmlInteractive => {
+            let html_table = HtmlTable::new(contents)
+                .has_header(has_header)
+                .standalone(args.html_standalone)
+                .interactive(matches!(args.format, TableOutputFmt::HtmlInteractive))
+                .theme(args.html_theme.clone())
+                .caption(args.html_caption.clone())
+                .id(args.html_id.clone())
+                .class(args.html_class.clone())
+                .to_html();
+            writer.write_all(html_table.as_bytes())?;
         }
-        TableOutputFmt::Html => {
-            let html_table = HtmlTable::from(contents).to_html_string();
-            let mut file = File::create(&args.output)?;
-            file.write_all(html_table.as_bytes())?;
+        TableOutputFmt::Json => {
+            let json_table = JsonTable::new(contents)
+                .has_header(has_header)
+                .layout(args.json_layout.clone())
+                .to_json()?;
+            writer.write_all(json_table.as_bytes())?;
+        }
+        TableOutputFmt::Jsonl => unreachable!("JSON Lines are written as rows are split"),
+        TableOutputFmt::Yaml => {
+            let yaml_table = YamlTable::new(contents).has_header(has_header).to_yaml()?;
+            writer.write_all(yaml_table.as_bytes())?;
+        }
+        TableOutputFmt::Toml => {
+            let toml_table = TomlTable::new(contents)
+                .has_header(has_header)
+                .table_name(&table_name(args))
+                .to_toml()?;
+            writer.write_all(toml_table.as_bytes())?;
         }
-        TableOutputFmt::Json => {}
         TableOutputFmt::Sql => {
             let sql_ddl_table = SqlDdlTable::new(contents)
-                .has_header(args.has_header)
+                .has_header(has_header)
+                .dialect(args.sql_dialect.clone())
+                .on_conflict(args.sql_on_conflict.clone())
+                .null_values(args.null_values.clone())
+                .table_name(&table_name(args))
+                .schema(args.schema.clone())
+                .batch_size(args.batch_size)
+                .transaction(args.transaction)
                 .to_sql()?;
-            let mut file = File::create(&args.output)?;
-            file.write_all(sql_ddl_table.as_bytes())?;
+            writer.write_all(sql_ddl_table.as_bytes())?;
         }
     }
-    Ok(())
+    writer.flush()?;
+    Ok(split.rejected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_path() {
+        assert_eq!(file_path(&None), None);
+        assert_eq!(file_path(&Some(PathBuf::from("-"))), None);
+        assert_eq!(
+            file_path(&Some(PathBuf::from("data.csv"))),
+            Some(Path::new("data.csv"))
+        );
+    }
 }
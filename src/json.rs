@@ -0,0 +1,362 @@
+//!
+//! JSON generation
+//!
+use crate::cli_args::JsonLayout;
+use crate::table::{column_names, name_columns, num_columns};
+use crate::types::ValueType;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum JsonError {
+    #[error("Empty contents in table")]
+    EmptyContents,
+}
+
+///
+/// Escape a value and wrap it in double quotes as a JSON string.
+///
+pub fn to_json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\u{08}' => escaped.push_str("\\b"),
+            '\u{0c}' => escaped.push_str("\\f"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+///
+/// Render an optional value as a JSON string, or null when missing.
+///
+fn to_json_value(value: Option<&String>) -> String {
+    value.map_or("null".to_string(), |v| to_json_string(v))
+}
+
+///
+/// Render an optional value as a JSON number, boolean, or string depending on its inferred type,
+/// or null when missing or empty.
+///
+pub fn to_json_typed_value(value: Option<&String>) -> String {
+    let Some(value) = value else {
+        return "null".to_string();
+    };
+    let trimmed = value.trim();
+    match ValueType::of(value) {
+        None => "null".to_string(),
+        Some(ValueType::Boolean) => trimmed.to_ascii_lowercase(),
+        Some(ValueType::Integer) => trimmed
+            .parse::<i64>()
+            .map_or_else(|_| to_json_string(value), |i| i.to_string()),
+        Some(ValueType::Real) => match trimmed.parse::<f64>() {
+            Ok(f) if f.is_finite() => f.to_string(),
+            _ => to_json_string(value),
+        },
+        Some(_) => to_json_string(value),
+    }
+}
+
+///
+/// Convert a row of values into a JSON array, padding missing values with null.
+///
+fn values_to_json_array(values: &[String], num_columns: usize) -> String {
+    format!(
+        "[{}]",
+        (0..num_columns)
+            .map(|i| to_json_value(values.get(i)))
+            .collect::<Vec<String>>()
+            .join(", ")
+    )
+}
+
+///
+/// Convert a row of values into a JSON object keyed by the column names.
+///
+fn values_to_json_object(values: &[String], names: &[String]) -> String {
+    format!(
+        "{{{}}}",
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| format!("{}: {}", to_json_string(name), to_json_value(values.get(i))))
+            .collect::<Vec<String>>()
+            .join(", ")
+    )
+}
+
+///
+/// Join rendered JSON elements into an array with one element per line.
+///
+fn to_json_block(elements: &[String], indent: &str) -> String {
+    if elements.is_empty() {
+        return "[]".to_string();
+    }
+    format!(
+        "[\n{}\n{}]",
+        elements
+            .iter()
+            .map(|e| format!("{}  {}", indent, e))
+            .collect::<Vec<String>>()
+            .join(",\n"),
+        indent
+    )
+}
+
+///
+/// JSON table
+///
+#[derive(Debug)]
+pub struct JsonTable {
+    contents: Vec<Vec<String>>,
+    has_header: bool,
+    layout: JsonLayout,
+}
+
+impl JsonTable {
+    ///
+    /// Create a new JSON table from a 2D vector of data.
+    ///
+    pub fn new(contents: Vec<Vec<String>>) -> Self {
+        JsonTable {
+            contents,
+            has_header: false,
+            layout: JsonLayout::Records,
+        }
+    }
+
+    ///
+    /// Set to true to use the first row of data as the header.
+    ///
+    pub fn has_header(&mut self, has_header: bool) -> &mut Self {
+        self.has_header = has_header;
+        self
+    }
+
+    ///
+    /// Set the layout of the JSON document.
+    ///
+    pub fn layout(&mut self, layout: JsonLayout) -> &mut Self {
+        self.layout = layout;
+        self
+    }
+
+    ///
+    /// Output the table as a JSON string.
+    ///
+    pub fn to_json(&self) -> Result<String, JsonError> {
+        let num_columns = num_columns(&self.contents);
+        let names = if self.has_header {
+            Some(column_names(&self.contents, true).ok_or(JsonError::EmptyContents)?)
+        } else {
+            None
+        };
+        let rows = self.contents.iter().skip(usize::from(self.has_header));
+
+        let rendered = match (&self.layout, &names) {
+            (JsonLayout::Records, Some(names)) => to_json_block(
+                &rows
+                    .map(|row| values_to_json_object(row, names))
+                    .collect::<Vec<String>>(),
+                "",
+            ),
+            (JsonLayout::Records, None) => to_json_block(
+                &rows
+                    .map(|row| values_to_json_array(row, num_columns))
+                    .collect::<Vec<String>>(),
+                "",
+            ),
+            (JsonLayout::Columns, names) => {
+                let rows = rows.collect::<Vec<_>>();
+                let columns = (0..num_columns)
+                    .map(|i| {
+                        format!(
+                            "[{}]",
+                            rows.iter()
+                                .map(|row| to_json_value(row.get(i)))
+                                .collect::<Vec<String>>()
+                                .join(", ")
+                        )
+                    })
+                    .collect::<Vec<String>>();
+                match names {
+                    Some(names) => format!(
+                        "{{\n{}\n}}",
+                        names
+                            .iter()
+                            .zip(columns.iter())
+                            .map(|(name, column)| format!("  {}: {}", to_json_string(name), column))
+                            .collect::<Vec<String>>()
+                            .join(",\n")
+                    ),
+                    None => to_json_block(&columns, ""),
+                }
+            }
+            (JsonLayout::Split, names) => {
+                let rows = rows
+                    .map(|row| values_to_json_array(row, num_columns))
+                    .collect::<Vec<String>>();
+                let columns = match names {
+                    Some(names) => format!(
+                        "  \"columns\": {},\n",
+                        values_to_json_array(names, num_columns)
+                    ),
+                    None => String::new(),
+                };
+                format!(
+                    "{{\n{}  \"rows\": {}\n}}",
+                    columns,
+                    to_json_block(&rows, "  ")
+                )
+            }
+        };
+
+        Ok(format!("{}\n", rendered))
+    }
+}
+
+///
+/// JSON Lines writer, rendering each row as a JSON object on its own line.
+///
+#[derive(Debug, Default)]
+pub struct JsonLines {
+    header: Option<Vec<String>>,
+    infer_types: bool,
+}
+
+impl JsonLines {
+    pub fn new() -> Self {
+        JsonLines::default()
+    }
+
+    ///
+    /// Set the header used as the keys of each object. Without a header, or for values beyond the
+    /// end of the header, columns are named by position.
+    ///
+    pub fn header(&mut self, header: Option<Vec<String>>) -> &mut Self {
+        self.header = header;
+        self
+    }
+
+    ///
+    /// Set to true to write numbers and booleans as typed JSON values rather than strings.
+    ///
+    pub fn infer_types(&mut self, infer_types: bool) -> &mut Self {
+        self.infer_types = infer_types;
+        self
+    }
+
+    ///
+    /// Output a row as a single line JSON object, without a line ending.
+    ///
+    pub fn to_json_line(&self, values: &[String]) -> String {
+        let header = self.header.as_deref().unwrap_or_default();
+        let names = name_columns(header, header.len().max(values.len()));
+        format!(
+            "{{{}}}",
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| {
+                    let value = if self.infer_types {
+                        to_json_typed_value(values.get(i))
+                    } else {
+                        to_json_value(values.get(i))
+                    };
+                    format!("{}: {}", to_json_string(name), value)
+                })
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::table::to_contents;
+
+    #[test]
+    fn test_json_string_escaping() {
+        assert_eq!(to_json_string(r#"a "b" c"#), r#""a \"b\" c""#);
+        assert_eq!(to_json_string("a\\b\n\t"), r#""a\\b\n\t""#);
+        assert_eq!(to_json_string("\u{01}"), r#""\u0001""#);
+    }
+
+    #[test]
+    fn test_json_layouts() {
+        let contents = to_contents(&[&["id", "name"], &["1", "a"], &["2"]]);
+
+        let records = JsonTable::new(contents.clone())
+            .has_header(true)
+            .to_json()
+            .unwrap();
+        assert_eq!(
+            records,
+            "[\n  {\"id\": \"1\", \"name\": \"a\"},\n  {\"id\": \"2\", \"name\": null}\n]\n"
+        );
+
+        let columns = JsonTable::new(contents.clone())
+            .has_header(true)
+            .layout(JsonLayout::Columns)
+            .to_json()
+            .unwrap();
+        assert_eq!(
+            columns,
+            "{\n  \"id\": [\"1\", \"2\"],\n  \"name\": [\"a\", null]\n}\n"
+        );
+
+        let split = JsonTable::new(contents)
+            .layout(JsonLayout::Split)
+            .to_json()
+            .unwrap();
+        assert_eq!(
+            split,
+            "{\n  \"rows\": [\n    [\"id\", \"name\"],\n    [\"1\", \"a\"],\n    [\"2\", null]\n  ]\n}\n"
+        );
+
+        let repeated = JsonTable::new(to_contents(&[&["id", "id"], &["1", "2"]]))
+            .has_header(true)
+            .to_json()
+            .unwrap();
+        assert_eq!(repeated, "[\n  {\"id\": \"1\", \"id_2\": \"2\"}\n]\n");
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let row = vec![
+            "1".to_string(),
+            "2.50".to_string(),
+            "TRUE".to_string(),
+            "".to_string(),
+        ];
+        let header = vec!["id".to_string(), "price".to_string()];
+
+        assert_eq!(
+            JsonLines::new().to_json_line(&row),
+            r#"{"col_1": "1", "col_2": "2.50", "col_3": "TRUE", "col_4": ""}"#
+        );
+        assert_eq!(
+            JsonLines::new()
+                .header(Some(header))
+                .infer_types(true)
+                .to_json_line(&row),
+            r#"{"id": 1, "price": 2.5, "col_3": true, "col_4": null}"#
+        );
+        assert_eq!(to_json_typed_value(Some(&"007".to_string())), r#""007""#);
+        assert_eq!(
+            JsonLines::new()
+                .header(Some(vec!["a".to_string(), "a".to_string()]))
+                .to_json_line(&row[..2]),
+            r#"{"a": "1", "a_2": "2.50"}"#
+        );
+    }
+}
@@ -2,31 +2,242 @@
 //! Text processing and parsing for individual lines
 //!
 
+use crate::cli_args::QuoteEscape;
+use crate::markdown::Alignment;
 use core::mem;
+use regex::Regex;
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
 use thiserror::Error;
 
 pub const DOUBLE_QUOTE: char = '\"';
+pub const SINGLE_QUOTE: char = '\'';
+pub const BACKTICK: char = '`';
+
+/// Vertical rules that separate the cells of a box-drawn table
+pub const BOX_VERTICAL_RULES: [char; 4] = ['|', '\u{2502}', '\u{2503}', '\u{2551}'];
+
+/// Horizontal rules that make up the border and separator lines of a box-drawn table
+const BOX_HORIZONTAL_RULES: [char; 5] = ['-', '=', '\u{2500}', '\u{2501}', '\u{2550}'];
+
+/// Numbered physical lines or records, read from memory or line by line from the input. Each is
+/// paired with the 1-based line number it starts on.
+pub type Records = Box<dyn Iterator<Item = io::Result<(usize, String)>>>;
+
+///
+/// Number the physical lines of contents already in memory.
+///
+pub fn numbered_lines(contents: &str) -> Records {
+    let lines = contents
+        .lines()
+        .enumerate()
+        .map(|(i, line)| Ok((i + 1, line.to_string())))
+        .collect::<Vec<_>>();
+    Box::new(lines.into_iter())
+}
+
+///
+/// Number the physical lines of a reader, reading each line only when it is needed.
+///
+pub fn read_lines<R: BufRead + 'static>(reader: R) -> Records {
+    Box::new(
+        reader
+            .lines()
+            .enumerate()
+            .map(|(i, line)| line.map(|line| (i + 1, line))),
+    )
+}
 
 ///
 /// Line parsing related errors.
 ///
 #[derive(Error, Debug)]
 pub enum ParseError {
-    #[error("Cannot parse line")]
-    CannotParseLine,
+    /// A quoted field is not closed before the end of the record. The column is the 1-based
+    /// character position of the opening quote within the record.
+    #[error("Unterminated quote")]
+    UnterminatedQuote { column: usize },
+    /// A record has a different number of fields to the first record.
+    #[error("Found {found} fields, expected {expected}")]
+    RaggedRow { expected: usize, found: usize },
+}
+
+impl ParseError {
+    ///
+    /// The 1-based character column within the record where the problem started.
+    ///
+    pub fn column(&self) -> usize {
+        match self {
+            ParseError::UnterminatedQuote { column } => *column,
+            ParseError::RaggedRow { .. } => 1,
+        }
+    }
 }
 
 ///
 /// Trait for lines that can be split into separate fields.
 ///
 pub trait Line {
-    fn split(&self, delimiters: &[char]) -> Result<Vec<String>, ParseError>;
+    /// The type describing where a line is split into fields
+    type Delimiters: ?Sized + ToOwned;
+
+    /// Additional options controlling how a line is split, e.g. quoting
+    type Options: Default;
+
+    fn split(&self, delimiters: &Self::Delimiters) -> Result<Vec<String>, ParseError>;
+
+    fn split_with(
+        &self,
+        delimiters: &Self::Delimiters,
+        _options: &Self::Options,
+    ) -> Result<Vec<String>, ParseError> {
+        self.split(delimiters)
+    }
+
+    ///
+    /// The text used to join fields of this line back together. By default a single space.
+    ///
+    fn separator(&self, _delimiters: &Self::Delimiters) -> String {
+        String::from(" ")
+    }
+
+    ///
+    /// Whether the line is a rule drawn between rows, rather than a row of fields. By default no
+    /// lines are rules.
+    ///
+    fn is_rule(&self) -> bool {
+        false
+    }
+
+    ///
+    /// Whether rules can be drawn between any rows. Otherwise only the second record can be a rule,
+    /// separating the header from the data. By default rules can be drawn anywhere.
+    ///
+    fn rules_between_rows() -> bool {
+        true
+    }
 
-    fn num_fields(&self, delimiters: &[char]) -> Result<usize, ParseError> {
+    fn num_fields(&self, delimiters: &Self::Delimiters) -> Result<usize, ParseError> {
         Ok(self.split(delimiters)?.len())
     }
 
+    ///
+    /// Join numbered physical lines into records, each paired with the line number it starts on.
+    /// By default each physical line is a record.
+    ///
+    fn records(lines: Records, _options: &Self::Options) -> Records {
+        lines
+    }
+
     fn new(line: &str) -> Self;
+
+    /// The original text of the line
+    fn as_str(&self) -> &str;
+}
+
+///
+/// Leave out physical lines that are empty or only whitespace, for layouts where a blank line is
+/// never a row.
+///
+fn skip_blank_lines(lines: Records) -> Records {
+    Box::new(lines.filter(|line| !matches!(line, Ok((_, line)) if line.trim().is_empty())))
+}
+
+///
+/// Whether a line is the row count that psql prints below a table, e.g. `(3 rows)`.
+///
+fn is_row_count(line: &str) -> bool {
+    line.trim()
+        .strip_prefix('(')
+        .and_then(|l| l.strip_suffix(" rows)").or_else(|| l.strip_suffix(" row)")))
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+///
+/// Records joined from physical lines while a quoted field is still open.
+///
+struct QuotedRecords {
+    lines: Records,
+    quoting: Quoting,
+    /// Physical lines to read again, after a quote that is never closed
+    replay: VecDeque<(usize, String)>,
+}
+
+impl QuotedRecords {
+    ///
+    /// Get the quote left open at the end of a line, given the quote open at its start.
+    ///
+    fn open_quote(&self, line: &str, mut open: Option<char>) -> Option<char> {
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match open {
+                None if self.quoting.quotes.contains(&c) => open = Some(c),
+                Some(q) if c == q => open = None,
+                Some(q)
+                    if c == '\\'
+                        && matches!(self.quoting.escape, QuoteEscape::Backslash)
+                        && chars.as_str().starts_with([q, '\\']) =>
+                {
+                    chars.next();
+                }
+                _ => {}
+            }
+        }
+        open
+    }
+}
+
+impl Iterator for QuotedRecords {
+    type Item = io::Result<(usize, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record: Vec<(usize, String)> = Vec::new();
+        let mut open: Option<char> = None;
+
+        loop {
+            let line = match self.replay.pop_front() {
+                Some(line) => line,
+                None => match self.lines.next() {
+                    Some(Ok(line)) => line,
+                    Some(Err(err)) => return Some(Err(err)),
+                    None if record.is_empty() => return None,
+                    // A quote that is never closed only spoils its own line, which fails to
+                    // split, and the lines after it are read again as records of their own
+                    None => {
+                        let first = record.remove(0);
+                        record
+                            .into_iter()
+                            .rev()
+                            .for_each(|l| self.replay.push_front(l));
+                        return Some(Ok(first));
+                    }
+                },
+            };
+            open = self.open_quote(&line.1, open);
+            record.push(line);
+            if open.is_none() {
+                let (line_number, _) = record[0];
+                let text = record
+                    .into_iter()
+                    .map(|(_, line)| line)
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                return Some(Ok((line_number, text)));
+            }
+        }
+    }
+}
+
+///
+/// Join numbered physical lines into records, joining lines while a quoted field is still open so
+/// that quoted fields may contain newlines.
+///
+pub fn split_quoted_records(lines: Records, quoting: &Quoting) -> Records {
+    Box::new(QuotedRecords {
+        lines,
+        quoting: quoting.clone(),
+        replay: VecDeque::new(),
+    })
 }
 
 ///
@@ -38,6 +249,15 @@ pub struct LineSplitContiguous {
 }
 
 impl Line for LineSplitContiguous {
+    type Delimiters = [char];
+    type Options = ();
+
+    fn separator(&self, delimiters: &[char]) -> String {
+        delimiters
+            .first()
+            .map_or(String::from(" "), |d| d.to_string())
+    }
+
     fn split(&self, delimiters: &[char]) -> Result<Vec<String>, ParseError> {
         Ok(self.line.split(delimiters).map(String::from).collect())
     }
@@ -47,6 +267,10 @@ impl Line for LineSplitContiguous {
             line: line.to_string(),
         }
     }
+
+    fn as_str(&self) -> &str {
+        &self.line
+    }
 }
 
 ///
@@ -58,6 +282,15 @@ pub struct LineIgnoreContiguous {
 }
 
 impl Line for LineIgnoreContiguous {
+    type Delimiters = [char];
+    type Options = ();
+
+    fn separator(&self, delimiters: &[char]) -> String {
+        delimiters
+            .first()
+            .map_or(String::from(" "), |d| d.to_string())
+    }
+
     fn split(&self, delimiters: &[char]) -> Result<Vec<String>, ParseError> {
         Ok(self
             .line
@@ -72,6 +305,10 @@ impl Line for LineIgnoreContiguous {
             line: line.to_string(),
         }
     }
+
+    fn as_str(&self) -> &str {
+        &self.line
+    }
 }
 
 enum CharState {
@@ -79,66 +316,165 @@ enum CharState {
     Delimiter,
     /// Character is within an unquoted field
     Unquoted,
-    /// Character is within a double quoted field
-    Quoted,
+    /// Character is within a field quoted by the given quote character, opened at the given column
+    Quoted(char, usize),
 }
 
 ///
-/// Double-quoted fields allowed. Contiguous delimiters are treated separately.
+/// Quoting options for lines with quoted fields.
 ///
-#[derive(Debug)]
-pub struct LineQuotedSplitContiguous {
-    line: String,
+#[derive(Debug, Clone)]
+pub struct Quoting {
+    /// Characters that can open and close a quoted field
+    pub quotes: Vec<char>,
+    /// How a quote character is escaped within a quoted field
+    pub escape: QuoteEscape,
+    /// Whether to keep the surrounding quotes in the field
+    pub keep_quotes: bool,
 }
 
-impl Line for LineQuotedSplitContiguous {
-    fn split(&self, delimiters: &[char]) -> Result<Vec<String>, ParseError> {
-        let mut fields = Vec::new();
-        let mut field = String::new();
-        let mut chars = self.line.chars();
-        let mut state = CharState::Delimiter;
+impl Default for Quoting {
+    fn default() -> Self {
+        Quoting {
+            quotes: vec![DOUBLE_QUOTE],
+            escape: QuoteEscape::Doubled,
+            keep_quotes: false,
+        }
+    }
+}
 
-        loop {
-            let c = chars.next();
-            state = match state {
-                CharState::Delimiter => match c {
-                    None => break,
-                    Some(DOUBLE_QUOTE) => CharState::Quoted,
-                    Some(c) if delimiters.contains(&c) => {
-                        fields.push(String::new());
-                        CharState::Delimiter
+///
+/// Split a line containing quoted fields using a character state machine.
+///
+fn split_quoted(
+    line: &str,
+    delimiters: &[char],
+    quoting: &Quoting,
+    ignore_contiguous: bool,
+) -> Result<Vec<String>, ParseError> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut state = CharState::Delimiter;
+    let mut column = 0;
+
+    loop {
+        let c = chars.next();
+        column += 1;
+        state = match state {
+            CharState::Delimiter => match c {
+                None => break,
+                Some(q) if quoting.quotes.contains(&q) => {
+                    if quoting.keep_quotes {
+                        field.push(q);
                     }
-                    Some(c) => {
-                        field.push(c);
-                        CharState::Unquoted
+                    CharState::Quoted(q, column)
+                }
+                Some(c) if delimiters.contains(&c) => {
+                    if !ignore_contiguous {
+                        fields.push(String::new());
                     }
-                },
-                CharState::Unquoted => match c {
-                    None => {
-                        fields.push(mem::take(&mut field));
-                        break;
+                    CharState::Delimiter
+                }
+                Some(c) => {
+                    field.push(c);
+                    CharState::Unquoted
+                }
+            },
+            CharState::Unquoted => match c {
+                None => {
+                    fields.push(mem::take(&mut field));
+                    break;
+                }
+                Some(q) if quoting.quotes.contains(&q) => {
+                    if quoting.keep_quotes {
+                        field.push(q);
                     }
-                    Some(DOUBLE_QUOTE) => CharState::Quoted,
-                    Some(c) if delimiters.contains(&c) => {
-                        fields.push(mem::take(&mut field));
-                        CharState::Delimiter
+                    CharState::Quoted(q, column)
+                }
+                Some(c) if delimiters.contains(&c) => {
+                    fields.push(mem::take(&mut field));
+                    CharState::Delimiter
+                }
+                Some(c) => {
+                    field.push(c);
+                    CharState::Unquoted
+                }
+            },
+            CharState::Quoted(q, start) => match c {
+                None => return Err(ParseError::UnterminatedQuote { column: start }),
+                Some(c) if c == q => match quoting.escape {
+                    QuoteEscape::Doubled if chars.next_if_eq(&q).is_some() => {
+                        column += 1;
+                        // Kept quotes keep their escape, so the field can be parsed again
+                        if quoting.keep_quotes {
+                            field.push(q);
+                        }
+                        field.push(q);
+                        CharState::Quoted(q, start)
                     }
-                    Some(c) => {
-                        field.push(c);
+                    _ => {
+                        if quoting.keep_quotes {
+                            field.push(q);
+                        }
                         CharState::Unquoted
                     }
                 },
-                CharState::Quoted => match c {
-                    None => return Err(ParseError::CannotParseLine),
-                    Some(DOUBLE_QUOTE) => CharState::Unquoted,
-                    Some(c) => {
-                        field.push(c);
-                        CharState::Quoted
+                Some('\\') if matches!(quoting.escape, QuoteEscape::Backslash) => {
+                    match chars.next_if(|&n| n == q || n == '\\') {
+                        Some(n) => {
+                            column += 1;
+                            if quoting.keep_quotes {
+                                field.push('\\');
+                            }
+                            field.push(n);
+                        }
+                        None => field.push('\\'),
                     }
-                },
-            }
+                    CharState::Quoted(q, start)
+                }
+                Some(c) => {
+                    field.push(c);
+                    CharState::Quoted(q, start)
+                }
+            },
         }
-        Ok(fields)
+    }
+    Ok(fields)
+}
+
+///
+/// Quoted fields allowed. Contiguous delimiters are treated separately.
+///
+#[derive(Debug)]
+pub struct LineQuotedSplitContiguous {
+    line: String,
+}
+
+impl Line for LineQuotedSplitContiguous {
+    type Delimiters = [char];
+    type Options = Quoting;
+
+    fn separator(&self, delimiters: &[char]) -> String {
+        delimiters
+            .first()
+            .map_or(String::from(" "), |d| d.to_string())
+    }
+
+    fn split(&self, delimiters: &[char]) -> Result<Vec<String>, ParseError> {
+        self.split_with(delimiters, &Quoting::default())
+    }
+
+    fn split_with(
+        &self,
+        delimiters: &[char],
+        quoting: &Quoting,
+    ) -> Result<Vec<String>, ParseError> {
+        split_quoted(&self.line, delimiters, quoting, false)
+    }
+
+    fn records(lines: Records, quoting: &Quoting) -> Records {
+        split_quoted_records(lines, quoting)
     }
 
     fn new(line: &str) -> Self {
@@ -146,10 +482,14 @@ impl Line for LineQuotedSplitContiguous {
             line: line.to_string(),
         }
     }
+
+    fn as_str(&self) -> &str {
+        &self.line
+    }
 }
 
 ///
-/// Double-quoted fields allowed. Contiguous delimiters are treated as a single delimiter.
+/// Quoted fields allowed. Contiguous delimiters are treated as a single delimiter.
 ///
 #[derive(Debug)]
 pub struct LineQuotedIgnoreContiguous {
@@ -157,57 +497,421 @@ pub struct LineQuotedIgnoreContiguous {
 }
 
 impl Line for LineQuotedIgnoreContiguous {
+    type Delimiters = [char];
+    type Options = Quoting;
+
+    fn separator(&self, delimiters: &[char]) -> String {
+        delimiters
+            .first()
+            .map_or(String::from(" "), |d| d.to_string())
+    }
+
     fn split(&self, delimiters: &[char]) -> Result<Vec<String>, ParseError> {
-        let mut fields = Vec::new();
-        let mut field = String::new();
-        let mut chars = self.line.chars();
-        let mut state = CharState::Delimiter;
+        self.split_with(delimiters, &Quoting::default())
+    }
 
-        loop {
-            let c = chars.next();
-            state = match state {
-                CharState::Delimiter => match c {
-                    None => break,
-                    Some(DOUBLE_QUOTE) => CharState::Quoted,
-                    Some(c) if delimiters.contains(&c) => CharState::Delimiter,
-                    Some(c) => {
-                        field.push(c);
-                        CharState::Unquoted
-                    }
-                },
-                CharState::Unquoted => match c {
-                    None => {
-                        fields.push(mem::take(&mut field));
-                        break;
-                    }
-                    Some(DOUBLE_QUOTE) => CharState::Quoted,
-                    Some(c) if delimiters.contains(&c) => {
-                        fields.push(mem::take(&mut field));
-                        CharState::Delimiter
-                    }
-                    Some(c) => {
-                        field.push(c);
-                        CharState::Unquoted
-                    }
-                },
-                CharState::Quoted => match c {
-                    None => return Err(ParseError::CannotParseLine),
-                    Some(DOUBLE_QUOTE) => CharState::Unquoted,
-                    Some(c) => {
-                        field.push(c);
-                        CharState::Quoted
-                    }
-                },
+    fn split_with(
+        &self,
+        delimiters: &[char],
+        quoting: &Quoting,
+    ) -> Result<Vec<String>, ParseError> {
+        split_quoted(&self.line, delimiters, quoting, true)
+    }
+
+    fn records(lines: Records, quoting: &Quoting) -> Records {
+        split_quoted_records(lines, quoting)
+    }
+
+    fn new(line: &str) -> Self {
+        LineQuotedIgnoreContiguous {
+            line: line.to_string(),
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        &self.line
+    }
+}
+
+///
+/// Split a line at every occurrence of any of the string delimiters, preferring the longest
+/// delimiter when several match at the same position.
+///
+fn split_str<'a>(line: &'a str, delimiters: &[String]) -> Vec<&'a str> {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while let Some(c) = line[i..].chars().next() {
+        let delimiter = delimiters
+            .iter()
+            .filter(|d| !d.is_empty() && line[i..].starts_with(d.as_str()))
+            .max_by_key(|d| d.len());
+        match delimiter {
+            Some(d) => {
+                fields.push(&line[start..i]);
+                i += d.len();
+                start = i;
             }
+            None => i += c.len_utf8(),
         }
-        Ok(fields)
+    }
+    fields.push(&line[start..]);
+    fields
+}
+
+///
+/// String delimiters. Contiguous delimiters are treated separately.
+///
+#[derive(Debug)]
+pub struct LineStrSplitContiguous {
+    line: String,
+}
+
+impl Line for LineStrSplitContiguous {
+    type Delimiters = [String];
+    type Options = ();
+
+    fn separator(&self, delimiters: &[String]) -> String {
+        delimiters
+            .first()
+            .cloned()
+            .unwrap_or_else(|| String::from(" "))
+    }
+
+    fn split(&self, delimiters: &[String]) -> Result<Vec<String>, ParseError> {
+        Ok(split_str(&self.line, delimiters)
+            .into_iter()
+            .map(String::from)
+            .collect())
     }
 
     fn new(line: &str) -> Self {
-        LineQuotedIgnoreContiguous {
+        LineStrSplitContiguous {
+            line: line.to_string(),
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        &self.line
+    }
+}
+
+///
+/// String delimiters. Contiguous delimiters are treated as a single delimiter.
+///
+#[derive(Debug)]
+pub struct LineStrIgnoreContiguous {
+    line: String,
+}
+
+impl Line for LineStrIgnoreContiguous {
+    type Delimiters = [String];
+    type Options = ();
+
+    fn separator(&self, delimiters: &[String]) -> String {
+        delimiters
+            .first()
+            .cloned()
+            .unwrap_or_else(|| String::from(" "))
+    }
+
+    fn split(&self, delimiters: &[String]) -> Result<Vec<String>, ParseError> {
+        Ok(split_str(&self.line, delimiters)
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect())
+    }
+
+    fn new(line: &str) -> Self {
+        LineStrIgnoreContiguous {
+            line: line.to_string(),
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        &self.line
+    }
+}
+
+///
+/// Regular expression delimiter. Contiguous delimiters are treated separately.
+///
+#[derive(Debug)]
+pub struct LineRegexSplitContiguous {
+    line: String,
+}
+
+impl Line for LineRegexSplitContiguous {
+    type Delimiters = Regex;
+    type Options = ();
+
+    fn separator(&self, delimiters: &Regex) -> String {
+        delimiters
+            .find(&self.line)
+            .map_or(String::from(" "), |m| m.as_str().to_string())
+    }
+
+    fn split(&self, delimiters: &Regex) -> Result<Vec<String>, ParseError> {
+        Ok(delimiters.split(&self.line).map(String::from).collect())
+    }
+
+    fn new(line: &str) -> Self {
+        LineRegexSplitContiguous {
+            line: line.to_string(),
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        &self.line
+    }
+}
+
+///
+/// Regular expression delimiter. Contiguous delimiters are treated as a single delimiter.
+///
+#[derive(Debug)]
+pub struct LineRegexIgnoreContiguous {
+    line: String,
+}
+
+impl Line for LineRegexIgnoreContiguous {
+    type Delimiters = Regex;
+    type Options = ();
+
+    fn separator(&self, delimiters: &Regex) -> String {
+        delimiters
+            .find(&self.line)
+            .map_or(String::from(" "), |m| m.as_str().to_string())
+    }
+
+    fn split(&self, delimiters: &Regex) -> Result<Vec<String>, ParseError> {
+        Ok(delimiters
+            .split(&self.line)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect())
+    }
+
+    fn new(line: &str) -> Self {
+        LineRegexIgnoreContiguous {
+            line: line.to_string(),
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        &self.line
+    }
+}
+
+///
+/// Fixed-width columns. Fields are split at character offsets and trimmed of padding.
+///
+#[derive(Debug)]
+pub struct LineFixedWidth {
+    line: String,
+}
+
+impl Line for LineFixedWidth {
+    /// Character offsets at which each column after the first starts
+    type Delimiters = [usize];
+    type Options = ();
+
+    fn split(&self, delimiters: &[usize]) -> Result<Vec<String>, ParseError> {
+        let chars = self.line.chars().collect::<Vec<char>>();
+        let mut starts = vec![0];
+        starts.extend(delimiters.iter().filter(|&&offset| offset > 0));
+        starts.sort_unstable();
+        starts.dedup();
+
+        Ok(starts
+            .iter()
+            .enumerate()
+            .map(|(i, &start)| {
+                let end = starts.get(i + 1).copied().unwrap_or(chars.len());
+                chars[start.min(chars.len())..end.min(chars.len())]
+                    .iter()
+                    .collect::<String>()
+                    .trim()
+                    .to_string()
+            })
+            .collect())
+    }
+
+    fn new(line: &str) -> Self {
+        LineFixedWidth {
             line: line.to_string(),
         }
     }
+
+    fn as_str(&self) -> &str {
+        &self.line
+    }
+}
+
+///
+/// Box-drawn table, as output by psql, the mysql client, or with Unicode box-drawing characters.
+/// Cells are split on vertical rules and trimmed of padding, and border lines are rules.
+///
+#[derive(Debug)]
+pub struct LineBoxed {
+    line: String,
+}
+
+impl Line for LineBoxed {
+    /// Vertical rules that separate the cells
+    type Delimiters = [char];
+    type Options = ();
+
+    fn split(&self, delimiters: &[char]) -> Result<Vec<String>, ParseError> {
+        let line = self.line.trim();
+        // Only strip an outer border when there are rules at both ends, so that an empty first
+        // or last cell of a table without outer borders (e.g. psql) is kept
+        let line = match (line.strip_prefix(delimiters), line.strip_suffix(delimiters)) {
+            (Some(inner), Some(_)) if !inner.is_empty() => inner.strip_suffix(delimiters).unwrap(),
+            _ => line,
+        };
+        Ok(line
+            .split(delimiters)
+            .map(|cell| cell.trim().to_string())
+            .collect())
+    }
+
+    fn separator(&self, _delimiters: &[char]) -> String {
+        String::from(" ")
+    }
+
+    ///
+    /// Border and separator lines are made only of rule and junction characters, e.g.
+    /// `+----+----+`, `----+----`, or `├──┼──┤`.
+    ///
+    fn is_rule(&self) -> bool {
+        let line = self.line.trim();
+        line.contains(BOX_HORIZONTAL_RULES)
+            && line
+                .chars()
+                .all(|c| matches!(c, '+' | '-' | '=' | '|' | ':' | '\u{2500}'..='\u{257F}'))
+    }
+
+    ///
+    /// Blank lines and the row count printed by psql are not rows.
+    ///
+    fn records(lines: Records, _options: &()) -> Records {
+        Box::new(
+            skip_blank_lines(lines)
+                .filter(|line| !matches!(line, Ok((_, line)) if is_row_count(line))),
+        )
+    }
+
+    fn new(line: &str) -> Self {
+        LineBoxed {
+            line: line.to_string(),
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        &self.line
+    }
+}
+
+///
+/// Markdown pipe table. Cells are split on unescaped pipes, with optional leading and trailing
+/// pipes, and trimmed of padding. The alignment row below the header is a rule.
+///
+#[derive(Debug)]
+pub struct LineMarkdown {
+    line: String,
+}
+
+impl LineMarkdown {
+    ///
+    /// Split the line on unescaped pipes, without unescaping or trimming the cells.
+    ///
+    fn cells(&self) -> Vec<&str> {
+        let line = self.line.trim();
+        let mut cells = Vec::new();
+        let mut start = 0;
+        let mut escaped = false;
+        for (i, c) in line.char_indices() {
+            match c {
+                '|' if !escaped => {
+                    cells.push(&line[start..i]);
+                    start = i + 1;
+                }
+                _ => escaped = c == '\\' && !escaped,
+            }
+        }
+        cells.push(&line[start..]);
+
+        if line.starts_with('|') {
+            cells.remove(0);
+        }
+        if cells.len() > 1 && cells.last().is_some_and(|cell| cell.is_empty()) {
+            cells.pop();
+        }
+        cells
+    }
+
+    ///
+    /// Get the column alignments, if the line is an alignment row.
+    ///
+    pub fn alignments(&self) -> Option<Vec<Alignment>> {
+        self.cells()
+            .into_iter()
+            .map(|cell| {
+                let cell = cell.trim();
+                let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+                if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                    return None;
+                }
+                Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+                    (true, true) => Alignment::Center,
+                    (true, false) => Alignment::Left,
+                    (false, true) => Alignment::Right,
+                    (false, false) => Alignment::None,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Line for LineMarkdown {
+    type Delimiters = ();
+    type Options = ();
+
+    fn split(&self, _delimiters: &()) -> Result<Vec<String>, ParseError> {
+        Ok(self
+            .cells()
+            .into_iter()
+            .map(|cell| cell.trim().replace("\\|", "|"))
+            .collect())
+    }
+
+    fn is_rule(&self) -> bool {
+        self.alignments().is_some()
+    }
+
+    fn rules_between_rows() -> bool {
+        false
+    }
+
+    ///
+    /// Blank lines are not rows, so that the alignment row is found below the header.
+    ///
+    fn records(lines: Records, _options: &()) -> Records {
+        skip_blank_lines(lines)
+    }
+
+    fn new(line: &str) -> Self {
+        LineMarkdown {
+            line: line.to_string(),
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        &self.line
+    }
 }
 
 #[cfg(test)]
@@ -245,4 +949,200 @@ mod tests {
         assert_eq!(ln3.num_fields(delims).unwrap(), 9);
         assert_eq!(ln4.num_fields(delims).unwrap(), 6);
     }
+
+    #[test]
+    fn test_line_split_quoting() {
+        let s = r#"'it''s',"say \"hi\"",`x,y`"#;
+
+        let delims = &[','];
+        let ln = LineQuotedSplitContiguous::new(s);
+        let doubled = Quoting {
+            quotes: vec![SINGLE_QUOTE, DOUBLE_QUOTE, BACKTICK],
+            escape: QuoteEscape::Doubled,
+            keep_quotes: false,
+        };
+        let backslash = Quoting {
+            escape: QuoteEscape::Backslash,
+            keep_quotes: true,
+            ..doubled.clone()
+        };
+
+        assert_eq!(
+            ln.split(delims).unwrap(),
+            vec!["'it''s'", r"say \hi\", "`x", "y`"]
+        );
+        assert_eq!(
+            ln.split_with(delims, &doubled).unwrap(),
+            vec!["it's", r"say \hi\", "x,y"]
+        );
+        assert_eq!(
+            ln.split_with(delims, &backslash).unwrap(),
+            vec!["'it''s'", r#""say \"hi\"""#, "`x,y`"]
+        );
+
+        let kept = Quoting {
+            keep_quotes: true,
+            ..doubled.clone()
+        };
+        assert_eq!(
+            LineQuotedSplitContiguous::new(r#""a""b",'c'"#)
+                .split_with(delims, &kept)
+                .unwrap(),
+            vec![r#""a""b""#, "'c'"]
+        );
+    }
+
+    #[test]
+    fn test_line_split_unterminated_quote() {
+        let ln = LineQuotedSplitContiguous::new(r#"a,"b""c,d"#);
+
+        let err = ln.split(&[',']).unwrap_err();
+
+        assert!(matches!(err, ParseError::UnterminatedQuote { column: 3 }));
+        assert_eq!(err.column(), 3);
+    }
+
+    #[test]
+    fn test_is_row_count() {
+        assert!(is_row_count("(1 row)"));
+        assert!(is_row_count("(12 rows)"));
+        assert!(!is_row_count("( rows)"));
+        assert!(!is_row_count("(1 row) extra"));
+    }
+
+    #[test]
+    fn test_split_quoted_records() {
+        let s = "a,\"b\nc\"\nd,\"e\"\"\nf\"\ng";
+
+        let records = split_quoted_records(numbered_lines(s), &Quoting::default())
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(
+            records,
+            vec![
+                (1, "a,\"b\nc\"".to_string()),
+                (3, "d,\"e\"\"\nf\"".to_string()),
+                (5, "g".to_string()),
+            ]
+        );
+        assert_eq!(
+            LineQuotedSplitContiguous::new(&records[1].1)
+                .split(&[','])
+                .unwrap(),
+            vec!["d", "e\"\nf"]
+        );
+    }
+
+    #[test]
+    fn test_split_unterminated_quote() {
+        let s = "item,desc\nLamp,\"desk\nlight\"\nTV,5\" screen\nRadio,small";
+
+        let records = split_quoted_records(numbered_lines(s), &Quoting::default())
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(
+            records,
+            vec![
+                (1, "item,desc".to_string()),
+                (2, "Lamp,\"desk\nlight\"".to_string()),
+                (4, "TV,5\" screen".to_string()),
+                (5, "Radio,small".to_string()),
+            ]
+        );
+        assert!(LineQuotedSplitContiguous::new(&records[2].1)
+            .split(&[','])
+            .is_err());
+    }
+
+    #[test]
+    fn test_line_split_str() {
+        let s = "a :: b ::::c -> d";
+
+        let delims = &["::".to_string(), "->".to_string()];
+        let ln1 = LineStrSplitContiguous::new(s);
+        let ln2 = LineStrIgnoreContiguous::new(s);
+
+        assert_eq!(
+            ln1.split(delims).unwrap(),
+            vec!["a ", " b ", "", "c ", " d"]
+        );
+        assert_eq!(ln2.num_fields(delims).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_line_split_regex() {
+        let s = "web-1   Running  2 days ago";
+
+        let delims = Regex::new(" {2,}").unwrap();
+        let ln = LineRegexSplitContiguous::new(s);
+
+        assert_eq!(
+            ln.split(&delims).unwrap(),
+            vec!["web-1", "Running", "2 days ago"]
+        );
+    }
+
+    #[test]
+    fn test_line_split_fixed_width() {
+        let s = "abc   2 days ago  x";
+
+        let ln = LineFixedWidth::new(s);
+
+        assert_eq!(ln.split(&[6, 18]).unwrap(), vec!["abc", "2 days ago", "x"]);
+        assert_eq!(
+            ln.split(&[6, 18, 25]).unwrap(),
+            vec!["abc", "2 days ago", "x", ""]
+        );
+    }
+
+    #[test]
+    fn test_line_split_boxed() {
+        let rules = &BOX_VERTICAL_RULES;
+
+        assert!(LineBoxed::new("+----+-------+").is_rule());
+        assert!(LineBoxed::new("----+-------").is_rule());
+        assert!(LineBoxed::new("\u{251c}\u{2500}\u{2500}\u{253c}\u{2500}\u{2524}").is_rule());
+        assert!(!LineBoxed::new("| -  | x     |").is_rule());
+        assert_eq!(
+            LineBoxed::new("| 1  | Smith |").split(rules).unwrap(),
+            vec!["1", "Smith"]
+        );
+        assert_eq!(
+            LineBoxed::new("\u{2502} 1 \u{2502} J \u{2502}")
+                .split(rules)
+                .unwrap(),
+            vec!["1", "J"]
+        );
+        assert_eq!(
+            LineBoxed::new("    | Smith").split(rules).unwrap(),
+            vec!["", "Smith"]
+        );
+    }
+
+    #[test]
+    fn test_line_split_markdown() {
+        let rule = LineMarkdown::new("|:---|--:| :-: | --- |");
+
+        assert!(rule.is_rule());
+        assert_eq!(
+            rule.alignments().unwrap(),
+            vec![
+                Alignment::Left,
+                Alignment::Right,
+                Alignment::Center,
+                Alignment::None
+            ]
+        );
+        assert!(!LineMarkdown::new("| - | x |").is_rule());
+        assert_eq!(
+            LineMarkdown::new(r"| a \| b |  | c |").split(&()).unwrap(),
+            vec!["a | b", "", "c"]
+        );
+        assert_eq!(
+            LineMarkdown::new("a | b").split(&()).unwrap(),
+            vec!["a", "b"]
+        );
+    }
 }
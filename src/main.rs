@@ -6,39 +6,103 @@
 
 #![allow(unused)]
 
+use crate::cli_args::Command;
 use clap::Parser;
+use log::info;
+use std::error::Error;
 use std::process;
+use table::LineError;
 
 mod cli_args;
+mod html;
 mod io;
+mod json;
 mod lines;
 mod markdown;
+mod sniff;
 mod sqlddl;
 mod table;
+mod toml;
+mod types;
+mod yaml;
 
 fn main() {
     let args = crate::cli_args::CliArgs::parse();
-    let delimiters_str = args
-        .delimiters
-        .iter()
-        .map(|d| d.to_string())
-        .collect::<Vec<_>>()
-        .join(" ");
-
-    println!("Format                : {}", args.format);
-    println!("Input path            : {}", args.input.display());
-    println!("Output path           : {}", args.output.display());
-    println!("Delimiters            : {}", delimiters_str);
-    println!("Contiguous delimiters : {}", args.contiguous_delimiters);
-    println!("Quoted fields         : {}", args.quoted_fields);
-    println!("Has header            : {}", args.has_header);
+    env_logger::Builder::new()
+        .filter_level(args.verbose.log_level_filter())
+        .format_timestamp(None)
+        .format_target(false)
+        .init();
+
+    if let Some(Command::FmtMd { files, check }) = &args.command {
+        let changed = crate::io::format_markdown_files(files, *check).unwrap_or_else(|err| {
+            eprintln!("Problem formatting markdown: {}", err);
+            process::exit(1);
+        });
+        if *check && changed {
+            process::exit(1);
+        }
+        return;
+    }
+
+    let delimiters_str = match (&args.delimiter_regex, args.delimiter_str.is_empty()) {
+        (Some(regex), _) => format!("Regex {:?}", regex.as_str()),
+        (None, false) => format!("Strings {:?}", args.delimiter_str),
+        (None, true) => args
+            .delimiters
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>()
+            .join(" "),
+    };
+
+    let input_str =
+        crate::io::file_path(&args.input).map_or("stdin".into(), |p| p.display().to_string());
+    let output_str =
+        crate::io::file_path(&args.output).map_or("stdout".into(), |p| p.display().to_string());
+
+    info!("Format                : {}", args.format);
+    info!("Input path            : {}", input_str);
+    info!("Output path           : {}", output_str);
+    info!("Input format          : {}", args.input_format);
+    info!("Delimiters            : {}", delimiters_str);
+    info!("Contiguous delimiters : {}", args.contiguous_delimiters);
+    info!("Quoted fields         : {}", args.quoted_fields);
+    if args.quoted_fields {
+        let quotes_str = args
+            .quote_chars
+            .iter()
+            .map(|q| q.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        info!("Quote characters      : {}", quotes_str);
+        info!("Quote escape          : {}", args.quote_escape);
+    }
+    let header_str = match (args.has_header, args.no_header) {
+        (true, _) => "true",
+        (_, true) => "false",
+        _ => "auto",
+    };
+    info!("Skip lines            : {}", args.skip_lines);
+    info!("Skip footer lines     : {}", args.skip_footer);
+    if let Some(prefix) = &args.comment_prefix {
+        info!("Comment prefix        : {:?}", prefix);
+    }
+    info!("Skip blank lines      : {}", args.skip_blank_lines);
+    info!("Has header            : {}", header_str);
+    info!("Auto-detect delimiters: {}", args.auto);
+    info!("Ragged rows           : {}", args.ragged);
+    info!("On error              : {}", args.on_error);
 
     let table = crate::io::read(&args).unwrap_or_else(|err| {
         eprintln!("Problem parsing input data: {}", err);
         process::exit(1);
     });
-    crate::io::write(&args, table).unwrap_or_else(|err| {
-        eprintln!("Problem writing output data: {}", err);
+    crate::io::write(&args, table).unwrap_or_else(|err: Box<dyn Error>| {
+        match err.downcast_ref::<LineError>() {
+            Some(err) => eprint!("Problem parsing input data:\n{}", err.diagnostic()),
+            None => eprintln!("Problem writing output data: {}", err),
+        }
         process::exit(1);
     });
 }
@@ -1,27 +1,90 @@
 //!
 //! Markdown table generation
 //!
+use crate::lines::{Line, LineMarkdown};
+use log::warn;
 use thiserror::Error;
 
 /// Minimum size for the width of a single column in the markdown table
 pub const MIN_COLUMN_WIDTH: usize = 3;
 
+///
+/// Alignment of a markdown column, set by colons in the separator row.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Alignment {
+    /// No colons (`---`)
+    #[default]
+    None,
+    /// Leading colon (`:--`)
+    Left,
+    /// Leading and trailing colons (`:-:`)
+    Center,
+    /// Trailing colon (`--:`)
+    Right,
+}
+
 #[derive(Error, Debug)]
 pub enum MarkdownError {
     #[error("Empty contents in table")]
     EmptyContents,
 }
 
+///
+/// Render a value for a markdown cell, which cannot contain newlines or unescaped pipes.
+///
+fn to_markdown_value(value: &str) -> String {
+    value
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\n', '\r'], "<br>")
+}
+
 ///
 /// Convert a row of values into a markdown string.
 ///
-fn values_to_markdown_row(values: &[String], col_widths: &[usize]) -> String {
+fn values_to_markdown_row(
+    values: &[String],
+    col_widths: &[usize],
+    alignments: &[Alignment],
+) -> String {
     format!(
         "|{}|",
         values
             .iter()
             .enumerate()
-            .map(|(i, value)| format!(" {:<width$} ", value, width = col_widths[i]))
+            .map(|(i, value)| {
+                let value = to_markdown_value(value);
+                let width = col_widths[i];
+                match alignments.get(i).copied().unwrap_or_default() {
+                    Alignment::None | Alignment::Left => format!(" {:<width$} ", value),
+                    Alignment::Center => format!(" {:^width$} ", value),
+                    Alignment::Right => format!(" {:>width$} ", value),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("|")
+    )
+}
+
+///
+/// Convert column alignments into the markdown separator row.
+///
+fn alignments_to_markdown_row(col_widths: &[usize], alignments: &[Alignment]) -> String {
+    format!(
+        "|{}|",
+        col_widths
+            .iter()
+            .enumerate()
+            .map(|(i, &width)| {
+                let dashes = |n: usize| "-".repeat(n);
+                match alignments.get(i).copied().unwrap_or_default() {
+                    Alignment::None => format!(" {} ", dashes(width)),
+                    Alignment::Left => format!(" :{} ", dashes(width - 1)),
+                    Alignment::Center => format!(" :{}: ", dashes(width - 2)),
+                    Alignment::Right => format!(" {}: ", dashes(width - 1)),
+                }
+            })
             .collect::<Vec<String>>()
             .join("|")
     )
@@ -48,6 +111,7 @@ fn repeated_to_markdown_row(value: &str, col_widths: &[usize]) -> String {
 pub struct MarkdownTable {
     contents: Vec<Vec<String>>,
     has_header: bool,
+    alignments: Vec<Alignment>,
 }
 
 impl MarkdownTable {
@@ -58,6 +122,7 @@ impl MarkdownTable {
         MarkdownTable {
             contents,
             has_header: false,
+            alignments: vec![],
         }
     }
 
@@ -69,6 +134,14 @@ impl MarkdownTable {
         self
     }
 
+    ///
+    /// Set the alignment of each column. Columns without an alignment are not aligned.
+    ///
+    pub fn alignments(&mut self, alignments: Vec<Alignment>) -> &mut Self {
+        self.alignments = alignments;
+        self
+    }
+
     ///
     /// Get the maximum number of columns needed to represent the table.
     ///
@@ -88,8 +161,9 @@ impl MarkdownTable {
 
         for line in self.contents.iter() {
             for (col_num, col_value) in line.iter().enumerate() {
-                if col_value.len() > col_widths[col_num] {
-                    col_widths[col_num] = col_value.len();
+                let width = to_markdown_value(col_value).chars().count();
+                if width > col_widths[col_num] {
+                    col_widths[col_num] = width;
                 };
             }
         }
@@ -104,24 +178,219 @@ impl MarkdownTable {
 
         let heading_rendered = if self.has_header {
             let values = self.contents.first().ok_or(MarkdownError::EmptyContents)?;
-            values_to_markdown_row(values, &col_widths)
+            values_to_markdown_row(values, &col_widths, &self.alignments)
         } else {
             repeated_to_markdown_row("?", &col_widths)
         };
-        let separator = repeated_to_markdown_row("-", &col_widths);
-        let contents_iter = if self.has_header {
-            self.contents.iter().skip(1)
-        } else {
-            self.contents.iter().skip(0)
-        };
-        let contents_rendered = contents_iter
-            .map(|row| values_to_markdown_row(row, &col_widths))
+        let separator = alignments_to_markdown_row(&col_widths, &self.alignments);
+        let contents_iter = self.contents.iter().skip(usize::from(self.has_header));
+        let rendered = [heading_rendered, separator]
+            .into_iter()
+            .chain(
+                contents_iter.map(|row| values_to_markdown_row(row, &col_widths, &self.alignments)),
+            )
             .collect::<Vec<String>>()
             .join("\n");
 
-        Ok(format!(
-            "{}\n{}\n{}\n",
-            heading_rendered, separator, contents_rendered
-        ))
+        Ok(format!("{}\n", rendered))
+    }
+}
+
+///
+/// Get the fence that opens or closes a fenced code block, e.g. "```" or "~~~~".
+///
+fn code_fence(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    ['`', '~'].into_iter().find_map(|c| {
+        let fence_len = trimmed.len() - trimmed.trim_start_matches(c).len();
+        (fence_len >= 3).then(|| &trimmed[..fence_len])
+    })
+}
+
+///
+/// Strip the line ending from a line of a document.
+///
+fn line_text(line: &str) -> &str {
+    line.trim_end_matches(['\n', '\r'])
+}
+
+///
+/// Render a pipe table found in a markdown document, indented like its header line. The lines
+/// include their line endings, and the line ending of the header is used for every line. A table
+/// with rows wider than its header is left unchanged, rather than losing the extra cells.
+///
+fn format_markdown_table(lines: &[&str], line_number: usize) -> Result<String, MarkdownError> {
+    let header = line_text(lines[0]);
+    let line_ending = &lines[0][header.len()..];
+    let indent = &header[..header.len() - header.trim_start().len()];
+    let alignments = LineMarkdown::new(line_text(lines[1]))
+        .alignments()
+        .unwrap_or_default();
+    let num_columns = alignments.len();
+
+    let mut contents = std::iter::once(lines[0])
+        .chain(lines[2..].iter().copied())
+        .map(|line| {
+            LineMarkdown::new(line_text(line))
+                .split(&())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<Vec<String>>>();
+    if contents.iter().any(|row| row.len() > num_columns) {
+        warn!(
+            "Line {}: table has rows with more cells than its header, leaving it unchanged",
+            line_number
+        );
+        return Ok(lines.concat());
+    }
+    for row in contents.iter_mut() {
+        row.resize(num_columns, String::new());
+    }
+    let rendered = MarkdownTable::new(contents)
+        .has_header(true)
+        .alignments(alignments)
+        .to_markdown()?;
+
+    let rendered = rendered
+        .lines()
+        .map(|line| format!("{}{}{}", indent, line, line_ending))
+        .collect::<String>();
+    // Keep a missing line ending at the end of the document
+    match lines.last() {
+        Some(last) if line_text(last) == *last => Ok(line_text(&rendered).to_string()),
+        _ => Ok(rendered),
+    }
+}
+
+///
+/// Whether the lines start with a pipe table: a header row and an alignment row with the same
+/// number of cells.
+///
+fn starts_markdown_table(lines: &[&str]) -> bool {
+    match lines {
+        [header, alignment, ..] if header.contains('|') => {
+            let num_columns = LineMarkdown::new(line_text(header))
+                .split(&())
+                .map_or(0, |row| row.len());
+            LineMarkdown::new(line_text(alignment))
+                .alignments()
+                .is_some_and(|alignments| alignments.len() == num_columns)
+        }
+        _ => false,
+    }
+}
+
+///
+/// Reformat every pipe table in a markdown document, skipping fenced code blocks. All other text
+/// is left unchanged.
+///
+pub fn format_markdown_tables(document: &str) -> Result<String, MarkdownError> {
+    let lines = document.split_inclusive('\n').collect::<Vec<&str>>();
+    let mut formatted = String::with_capacity(document.len());
+    let mut fence: Option<&str> = None;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = line_text(lines[i]);
+        match (fence, code_fence(line)) {
+            (Some(open), Some(close)) if close.starts_with(open) => fence = None,
+            (Some(_), _) => {}
+            (None, Some(open)) => fence = Some(open),
+            (None, None) if starts_markdown_table(&lines[i..]) => {
+                let end = (i + 2..lines.len())
+                    .find(|&j| {
+                        let line = line_text(lines[j]);
+                        line.trim().is_empty() || !line.contains('|') || code_fence(line).is_some()
+                    })
+                    .unwrap_or(lines.len());
+                formatted.push_str(&format_markdown_table(&lines[i..end], i + 1)?);
+                i = end;
+                continue;
+            }
+            (None, None) => {}
+        }
+        formatted.push_str(lines[i]);
+        i += 1;
+    }
+    Ok(formatted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_markdown_tables() {
+        let document = "\
+# Title
+
+Some | text without a table.
+
+  a|b
+  :--|--:
+  x|1
+  longer|
+
+| a | b |
+|---|---|
+| 1 | 2 | 3 |
+
+```
+c|d
+-|-
+```
+";
+
+        assert_eq!(
+            format_markdown_tables(document).unwrap(),
+            "\
+# Title
+
+Some | text without a table.
+
+  | a      |   b |
+  | :----- | --: |
+  | x      |   1 |
+  | longer |     |
+
+| a | b |
+|---|---|
+| 1 | 2 | 3 |
+
+```
+c|d
+-|-
+```
+"
+        );
+    }
+
+    #[test]
+    fn test_format_markdown_table_wide_row() {
+        let document = "| a | b |\n|---|---|\n| `x|y` | 2 |\n";
+
+        assert_eq!(format_markdown_tables(document).unwrap(), document);
+    }
+
+    #[test]
+    fn test_to_markdown_alignments() {
+        let contents = vec![
+            vec!["name".to_string(), "qty".to_string(), "note".to_string()],
+            vec!["a|b".to_string(), "1".to_string(), "x".to_string()],
+        ];
+
+        let md = MarkdownTable::new(contents)
+            .has_header(true)
+            .alignments(vec![Alignment::Left, Alignment::Right, Alignment::Center])
+            .to_markdown()
+            .unwrap();
+
+        assert_eq!(
+            md,
+            "| name | qty | note |\n| :--- | --: | :--: |\n| a\\|b |   1 |  x   |\n"
+        );
     }
 }
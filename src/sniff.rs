@@ -0,0 +1,132 @@
+//!
+//! Automatic detection of delimiters and quoting
+//!
+
+use crate::lines::{
+    Line, LineIgnoreContiguous, LineQuotedIgnoreContiguous, LineQuotedSplitContiguous,
+    LineSplitContiguous,
+};
+
+/// Default number of non-blank lines sampled when detecting the table configuration
+pub const DEFAULT_SAMPLE_LINES: usize = 20;
+
+/// Candidate delimiters, in order of preference when configurations are equally consistent
+const CANDIDATE_DELIMITERS: [char; 5] = [',', '\t', '|', ':', ' '];
+
+///
+/// Configuration detected from a sample of lines.
+///
+#[derive(Debug, PartialEq)]
+pub struct Sniffed {
+    pub delimiters: Vec<char>,
+    pub contiguous_delimiters: bool,
+    pub quoted_fields: bool,
+    /// Fraction of sampled lines with the most common number of fields
+    pub confidence: f64,
+}
+
+///
+/// Count the fields in each line, or None if any line cannot be parsed.
+///
+fn field_counts<T: Line<Delimiters = [char]>>(
+    lines: &[&str],
+    delimiters: &[char],
+) -> Option<Vec<usize>> {
+    lines
+        .iter()
+        .map(|line| T::new(line).num_fields(delimiters).ok())
+        .collect()
+}
+
+///
+/// Get the most common field count and the fraction of lines that have it.
+///
+fn modal_count(counts: &[usize]) -> (usize, f64) {
+    let mut modal = (0, 0);
+    for &count in counts {
+        let frequency = counts.iter().filter(|&&c| c == count).count();
+        if frequency > modal.1 || (frequency == modal.1 && count > modal.0) {
+            modal = (count, frequency);
+        }
+    }
+    (modal.0, modal.1 as f64 / counts.len().max(1) as f64)
+}
+
+///
+/// Detect the delimiter, contiguous delimiter handling, and quoting that give the most consistent
+/// number of fields across the first lines of the contents. Returns None if no configuration
+/// splits the lines into more than one field.
+///
+pub fn sniff(contents: &str, sample_lines: usize) -> Option<Sniffed> {
+    let lines = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(sample_lines)
+        .collect::<Vec<&str>>();
+
+    let mut best: Option<(Sniffed, usize)> = None;
+    for delimiter in CANDIDATE_DELIMITERS {
+        let delimiters = [delimiter];
+        for (contiguous_delimiters, quoted_fields) in
+            [(false, false), (true, false), (false, true), (true, true)]
+        {
+            let counts = match (contiguous_delimiters, quoted_fields) {
+                (false, false) => field_counts::<LineSplitContiguous>(&lines, &delimiters),
+                (true, false) => field_counts::<LineIgnoreContiguous>(&lines, &delimiters),
+                (false, true) => field_counts::<LineQuotedSplitContiguous>(&lines, &delimiters),
+                (true, true) => field_counts::<LineQuotedIgnoreContiguous>(&lines, &delimiters),
+            };
+            let Some(counts) = counts else {
+                continue;
+            };
+            let (num_fields, confidence) = modal_count(&counts);
+            if num_fields < 2 {
+                continue;
+            }
+            let is_better = best.as_ref().is_none_or(|(b, b_fields)| {
+                confidence > b.confidence || (confidence == b.confidence && num_fields > *b_fields)
+            });
+            if is_better {
+                let sniffed = Sniffed {
+                    delimiters: delimiters.to_vec(),
+                    contiguous_delimiters,
+                    quoted_fields,
+                    confidence,
+                };
+                best = Some((sniffed, num_fields));
+            }
+        }
+    }
+    best.map(|(sniffed, _)| sniffed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_quoted_csv() {
+        let s = "id,name,notes\n1,\"Smith, J\",x\n2,Jones,\"a b\"\n";
+        let sniffed = sniff(s, DEFAULT_SAMPLE_LINES).unwrap();
+
+        assert_eq!(sniffed.delimiters, vec![',']);
+        assert!(!sniffed.contiguous_delimiters);
+        assert!(sniffed.quoted_fields);
+        assert_eq!(sniffed.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_sniff_aligned_spaces() {
+        let s = "NAME    READY   STATUS\nweb-1   1/1     Running\ndb-0    0/1     Pending\n";
+        let sniffed = sniff(s, DEFAULT_SAMPLE_LINES).unwrap();
+
+        assert_eq!(sniffed.delimiters, vec![' ']);
+        assert!(sniffed.contiguous_delimiters);
+        assert!(!sniffed.quoted_fields);
+    }
+
+    #[test]
+    fn test_sniff_single_column() {
+        assert_eq!(sniff("a\nb\nc\n", DEFAULT_SAMPLE_LINES), None);
+    }
+}
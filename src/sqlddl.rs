@@ -1,12 +1,176 @@
 //!
 //! SQL DDL generation
 //!
+use crate::cli_args::{SqlDialect, SqlOnConflict};
+use crate::table::{column_names, num_columns};
+use crate::types::{ColumnType, ValueType};
+use std::collections::HashMap;
+use std::mem;
 use thiserror::Error;
 
+/// Longest VARCHAR column before falling back to TEXT
+pub const MAX_VARCHAR_LENGTH: usize = 255;
+
+/// Table name used when no other name is available
+pub const DEFAULT_TABLE_NAME: &str = "table_name";
+
 #[derive(Error, Debug)]
 pub enum SqlDdlError {
     #[error("Empty contents in table")]
     EmptyContents,
+    #[error("Conflict handling '{0}' is not supported by the {1} dialect")]
+    UnsupportedConflict(SqlOnConflict, SqlDialect),
+    #[error("Conflict handling needs a primary key, but the first column '{0}' has empty values")]
+    NullableKey(String),
+}
+
+///
+/// Convert any name (e.g. a file name) into a valid unquoted SQL identifier.
+///
+pub fn sanitize_identifier(name: &str) -> String {
+    let mut identifier = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if identifier.is_empty() {
+        return DEFAULT_TABLE_NAME.to_string();
+    }
+    if identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+    identifier
+}
+
+impl SqlDialect {
+    ///
+    /// Quote an identifier such as a table or column name.
+    ///
+    fn quote_identifier(&self, name: &str) -> String {
+        match self {
+            SqlDialect::Mysql => format!("`{}`", name.replace('`', "``")),
+            SqlDialect::Mssql => format!("[{}]", name.replace(']', "]]")),
+            _ => format!("\"{}\"", name.replace('"', "\"\"")),
+        }
+    }
+
+    ///
+    /// Get the name of the column type used to store the inferred column type.
+    ///
+    fn type_name(&self, column_type: &ColumnType) -> String {
+        use SqlDialect::*;
+        let varchar = column_type.max_length <= MAX_VARCHAR_LENGTH;
+        let length = column_type.max_length.max(1);
+        match (column_type.value_type, self) {
+            (ValueType::Boolean, Sqlite) => "INTEGER".to_string(),
+            (ValueType::Boolean, Mssql) => "BIT".to_string(),
+            (ValueType::Boolean, _) => "BOOLEAN".to_string(),
+            (ValueType::Integer, Generic | Sqlite) => "INTEGER".to_string(),
+            (ValueType::Integer, _) => "BIGINT".to_string(),
+            (ValueType::Real, Postgres) => "DOUBLE PRECISION".to_string(),
+            (ValueType::Real, Mysql) => "DOUBLE".to_string(),
+            (ValueType::Real, Mssql) => "FLOAT".to_string(),
+            (ValueType::Real, _) => "REAL".to_string(),
+            (ValueType::Date, Sqlite) => "TEXT".to_string(),
+            (ValueType::Date, _) => "DATE".to_string(),
+            (ValueType::Timestamp, Sqlite) => "TEXT".to_string(),
+            (ValueType::Timestamp, Mysql) => "DATETIME".to_string(),
+            (ValueType::Timestamp, Mssql) => "DATETIME2".to_string(),
+            (ValueType::Timestamp, _) => "TIMESTAMP".to_string(),
+            (ValueType::Text, Sqlite) => "TEXT".to_string(),
+            (ValueType::Text, Mssql) if varchar => format!("NVARCHAR({})", length),
+            (ValueType::Text, Mssql) => "NVARCHAR(MAX)".to_string(),
+            (ValueType::Text, _) if varchar => format!("VARCHAR({})", length),
+            (ValueType::Text, _) => "TEXT".to_string(),
+        }
+    }
+
+    ///
+    /// Escape a value and wrap it in single quotes as a string literal.
+    ///
+    fn string_literal(&self, value: &str) -> String {
+        let has_newlines = value.contains(['\n', '\r']);
+        match self {
+            SqlDialect::Mysql => format!(
+                "'{}'",
+                value
+                    .replace('\\', "\\\\")
+                    .replace('\'', "''")
+                    .replace('\n', "\\n")
+                    .replace('\r', "\\r")
+            ),
+            SqlDialect::Postgres if has_newlines => format!(
+                "E'{}'",
+                value
+                    .replace('\\', "\\\\")
+                    .replace('\'', "''")
+                    .replace('\n', "\\n")
+                    .replace('\r', "\\r")
+            ),
+            SqlDialect::Sqlite | SqlDialect::Mssql if has_newlines => self.concat_newlines(value),
+            SqlDialect::Mssql => format!("N'{}'", value.replace('\'', "''")),
+            _ => format!("'{}'", value.replace('\'', "''")),
+        }
+    }
+
+    ///
+    /// Build a string literal containing newlines by concatenating quoted strings with newline
+    /// characters, for dialects without escape sequences in string literals.
+    ///
+    fn concat_newlines(&self, value: &str) -> String {
+        let (concat, char_fn) = match self {
+            SqlDialect::Mssql => (" + ", "NCHAR"),
+            _ => (" || ", "char"),
+        };
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        for c in value.chars() {
+            match c {
+                '\n' | '\r' => {
+                    if !text.is_empty() {
+                        parts.push(self.string_literal(&mem::take(&mut text)));
+                    }
+                    parts.push(format!("{}({})", char_fn, c as u32));
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(self.string_literal(&text));
+        }
+        parts.join(concat)
+    }
+
+    ///
+    /// Get the literal used for a boolean value.
+    ///
+    fn boolean_literal(&self, value: bool) -> &'static str {
+        match (self, value) {
+            (SqlDialect::Sqlite | SqlDialect::Mssql, true) => "1",
+            (SqlDialect::Sqlite | SqlDialect::Mssql, false) => "0",
+            (_, true) => "TRUE",
+            (_, false) => "FALSE",
+        }
+    }
+
+    ///
+    /// Get the statement that starts a transaction.
+    ///
+    fn begin_transaction(&self) -> &'static str {
+        match self {
+            SqlDialect::Mssql => "BEGIN TRANSACTION;",
+            _ => "BEGIN;",
+        }
+    }
+
+    ///
+    /// Maximum number of rows allowed in a single multi-row INSERT statement.
+    ///
+    fn max_rows_per_insert(&self) -> Option<usize> {
+        match self {
+            SqlDialect::Mssql => Some(1000),
+            _ => None,
+        }
+    }
 }
 
 ///
@@ -16,6 +180,13 @@ pub enum SqlDdlError {
 pub struct SqlDdlTable {
     contents: Vec<Vec<String>>,
     has_header: bool,
+    dialect: SqlDialect,
+    on_conflict: SqlOnConflict,
+    null_values: Vec<String>,
+    table_name: String,
+    schema: Option<String>,
+    batch_size: Option<usize>,
+    transaction: bool,
 }
 
 impl SqlDdlTable {
@@ -26,6 +197,13 @@ impl SqlDdlTable {
         SqlDdlTable {
             contents,
             has_header: false,
+            dialect: SqlDialect::Generic,
+            on_conflict: SqlOnConflict::Error,
+            null_values: vec![],
+            table_name: DEFAULT_TABLE_NAME.to_string(),
+            schema: None,
+            batch_size: None,
+            transaction: false,
         }
     }
 
@@ -38,42 +216,546 @@ impl SqlDdlTable {
     }
 
     ///
-    /// Get the maximum number of columns needed to represent the table.
+    /// Set the SQL dialect used for quoting, type names, and literals.
+    ///
+    pub fn dialect(&mut self, dialect: SqlDialect) -> &mut Self {
+        self.dialect = dialect;
+        self
+    }
+
+    ///
+    /// Set how INSERT statements handle rows that conflict with existing rows.
+    ///
+    pub fn on_conflict(&mut self, on_conflict: SqlOnConflict) -> &mut Self {
+        self.on_conflict = on_conflict;
+        self
+    }
+
+    ///
+    /// Set the values, in addition to empty values, that are output as NULL.
+    ///
+    pub fn null_values(&mut self, null_values: Vec<String>) -> &mut Self {
+        self.null_values = null_values;
+        self
+    }
+
+    ///
+    /// Set the name of the table to create and insert into.
+    ///
+    pub fn table_name(&mut self, table_name: &str) -> &mut Self {
+        self.table_name = table_name.to_string();
+        self
+    }
+
+    ///
+    /// Set the schema that the table belongs to.
+    ///
+    pub fn schema(&mut self, schema: Option<String>) -> &mut Self {
+        self.schema = schema;
+        self
+    }
+
+    ///
+    /// Set the maximum number of rows in each INSERT statement. All rows are inserted in a single
+    /// statement if not set, unless limited by the dialect.
+    ///
+    pub fn batch_size(&mut self, batch_size: Option<usize>) -> &mut Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    ///
+    /// Set to true to wrap all statements in a transaction.
+    ///
+    pub fn transaction(&mut self, transaction: bool) -> &mut Self {
+        self.transaction = transaction;
+        self
+    }
+
+    ///
+    /// Get the quoted table name, including the schema if set.
+    ///
+    fn get_qualified_table_name(&self) -> String {
+        let table_name = self.dialect.quote_identifier(&self.table_name);
+        match &self.schema {
+            Some(schema) => format!("{}.{}", self.dialect.quote_identifier(schema), table_name),
+            None => table_name,
+        }
+    }
+
+    ///
+    /// Check whether a value should be output as NULL.
     ///
-    fn get_num_columns(&self) -> usize {
-        self.contents
+    fn is_null(&self, value: &str) -> bool {
+        value.is_empty() || self.null_values.iter().any(|null| null == value)
+    }
+
+    ///
+    /// Get the column names from the header, or name columns by position if there is no header.
+    /// A table needs at least one column.
+    ///
+    fn get_column_names(&self) -> Result<Vec<String>, SqlDdlError> {
+        column_names(&self.contents, self.has_header)
+            .filter(|names| !names.is_empty())
+            .ok_or(SqlDdlError::EmptyContents)
+    }
+
+    ///
+    /// Infer the type of each column from the values in all rows.
+    ///
+    fn get_column_types(&self) -> Vec<ColumnType> {
+        (0..num_columns(&self.contents))
+            .map(|i| {
+                ColumnType::infer(self.contents.iter().skip(usize::from(self.has_header)).map(
+                    |row| match row.get(i) {
+                        Some(value) if !self.is_null(value) => value.as_str(),
+                        _ => "",
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    ///
+    /// Render a single value as a SQL literal for a column of the given type.
+    ///
+    fn to_sql_value(&self, value: Option<&String>, column_type: &ColumnType) -> String {
+        let value = match value {
+            Some(value) if !self.is_null(value) => value,
+            _ => return "NULL".to_string(),
+        };
+        match column_type.value_type {
+            ValueType::Integer | ValueType::Real => value.trim().to_string(),
+            ValueType::Boolean if value.trim().eq_ignore_ascii_case("true") => {
+                self.dialect.boolean_literal(true).to_string()
+            }
+            ValueType::Boolean if value.trim().eq_ignore_ascii_case("false") => {
+                self.dialect.boolean_literal(false).to_string()
+            }
+            _ => self.dialect.string_literal(value),
+        }
+    }
+
+    ///
+    /// Whether the first column is a primary key, which rows conflict on when conflicting rows are
+    /// ignored or replaced.
+    ///
+    fn has_key(&self) -> bool {
+        !matches!(self.on_conflict, SqlOnConflict::Error)
+    }
+
+    ///
+    /// Output the table as a CREATE TABLE statement with inferred column types. When conflicting
+    /// rows are ignored or replaced, the first column is the primary key that rows conflict on, and
+    /// the table may already exist.
+    ///
+    fn to_create_table(&self, names: &[String], column_types: &[ColumnType]) -> String {
+        let has_key = self.has_key();
+        let columns_rendered = names
             .iter()
-            .map(|line| line.len())
-            .max()
-            .unwrap_or(0)
+            .zip(column_types)
+            .enumerate()
+            .map(|(i, (name, column_type))| {
+                let constraint = match (has_key && i == 0, column_type.nullable) {
+                    (true, _) => "PRIMARY KEY",
+                    (false, true) => "NULL",
+                    (false, false) => "NOT NULL",
+                };
+                format!(
+                    "    {} {} {}",
+                    self.dialect.quote_identifier(name),
+                    self.dialect.type_name(column_type),
+                    constraint
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(",\n");
+
+        format!(
+            "CREATE TABLE {}{} (\n{}\n);\n",
+            if has_key { "IF NOT EXISTS " } else { "" },
+            self.get_qualified_table_name(),
+            columns_rendered
+        )
     }
 
     ///
-    /// Output the table as SQL DDL statement.
+    /// Get the start of the INSERT statement, depending on the dialect and conflict handling.
+    ///
+    fn to_insert_prefix(&self) -> Result<&'static str, SqlDdlError> {
+        use SqlDialect::*;
+        match (&self.on_conflict, &self.dialect) {
+            (SqlOnConflict::Error, _) | (_, Postgres) => Ok("INSERT INTO"),
+            (SqlOnConflict::Ignore, Mysql) => Ok("INSERT IGNORE INTO"),
+            (SqlOnConflict::Replace, Mysql) => Ok("REPLACE INTO"),
+            (SqlOnConflict::Ignore, Sqlite) => Ok("INSERT OR IGNORE INTO"),
+            (SqlOnConflict::Replace, Sqlite) => Ok("INSERT OR REPLACE INTO"),
+            (on_conflict, dialect) => Err(SqlDdlError::UnsupportedConflict(
+                on_conflict.clone(),
+                dialect.clone(),
+            )),
+        }
+    }
+
+    ///
+    /// Get the end of the INSERT statement. PostgreSQL uses the first column as the conflict target.
+    ///
+    fn to_insert_suffix(&self, names: &[String]) -> String {
+        let quoted = names
+            .iter()
+            .map(|name| self.dialect.quote_identifier(name))
+            .collect::<Vec<String>>();
+        match (&self.on_conflict, &self.dialect, quoted.split_first()) {
+            (SqlOnConflict::Ignore, SqlDialect::Postgres, _) => {
+                "\nON CONFLICT DO NOTHING".to_string()
+            }
+            (SqlOnConflict::Replace, SqlDialect::Postgres, Some((key, rest)))
+                if !rest.is_empty() =>
+            {
+                format!(
+                    "\nON CONFLICT ({}) DO UPDATE SET {}",
+                    key,
+                    rest.iter()
+                        .map(|name| format!("{} = EXCLUDED.{}", name, name))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )
+            }
+            (SqlOnConflict::Replace, SqlDialect::Postgres, Some((key, _))) => {
+                format!("\nON CONFLICT ({}) DO NOTHING", key)
+            }
+            _ => String::new(),
+        }
+    }
+
+    ///
+    /// Output the table as SQL DDL statements.
     ///
     pub fn to_sql(&self) -> Result<String, SqlDdlError> {
-        let header_names = if self.has_header {
-            self.contents
-                .first()
-                .ok_or(SqlDdlError::EmptyContents)?
-                .clone()
+        let header_names = self.get_column_names()?;
+        let column_types = self.get_column_types();
+        if self.has_key() && column_types.first().is_some_and(|t| t.nullable) {
+            return Err(SqlDdlError::NullableKey(header_names[0].clone()));
+        }
+        let insert_prefix = self.to_insert_prefix()?;
+        let insert_suffix = self.to_insert_suffix(&header_names);
+        let columns_rendered = header_names
+            .iter()
+            .map(|name| self.dialect.quote_identifier(name))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let rows_rendered = self
+            .contents
+            .iter()
+            .skip(usize::from(self.has_header))
+            .map(|row| {
+                column_types
+                    .iter()
+                    .enumerate()
+                    .map(|(i, column_type)| self.to_sql_value(row.get(i), column_type))
+                    .collect::<Vec<String>>()
+            })
+            .collect::<Vec<Vec<String>>>();
+        // PostgreSQL cannot update the same row twice in one statement, so only the last row
+        // with each key is kept in a batch, as if the earlier rows had been replaced
+        let dedupe = matches!(
+            (&self.on_conflict, &self.dialect),
+            (SqlOnConflict::Replace, SqlDialect::Postgres)
+        );
+        let batch_size = match (self.batch_size, self.dialect.max_rows_per_insert()) {
+            (Some(size), Some(max)) => size.min(max),
+            (size, max) => size.or(max).unwrap_or(rows_rendered.len()),
+        }
+        .max(1);
+        let inserts_rendered = rows_rendered
+            .chunks(batch_size)
+            .map(|rows| {
+                let last = rows
+                    .iter()
+                    .enumerate()
+                    .map(|(i, values)| (&values[0], i))
+                    .collect::<HashMap<_, _>>();
+                let rows = rows
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, values)| !dedupe || last[&values[0]] == *i)
+                    .map(|(_, values)| format!("({})", values.join(", ")))
+                    .collect::<Vec<String>>();
+                format!(
+                    "{} {}\n({})\nVALUES\n{}{};\n",
+                    insert_prefix,
+                    self.get_qualified_table_name(),
+                    columns_rendered,
+                    rows.join(",\n"),
+                    insert_suffix,
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let statements = format!(
+            "{}\n{}",
+            self.to_create_table(&header_names, &column_types),
+            inserts_rendered,
+        );
+
+        if self.transaction {
+            Ok(format!(
+                "{}\n\n{}\nCOMMIT;\n",
+                self.dialect.begin_transaction(),
+                statements
+            ))
         } else {
-            vec!["?".to_string(); self.get_num_columns()]
+            Ok(statements)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::table::to_contents;
+
+    #[test]
+    fn test_sql_string_literal() {
+        assert_eq!(SqlDialect::Generic.string_literal("O'Brien"), "'O''Brien'");
+        assert_eq!(SqlDialect::Mysql.string_literal(r"a\'b"), r"'a\\''b'");
+        assert_eq!(SqlDialect::Mssql.string_literal("é"), "N'é'");
+        assert_eq!(SqlDialect::Postgres.string_literal("a\nb"), r"E'a\nb'");
+        assert_eq!(
+            SqlDialect::Sqlite.string_literal("a\nb"),
+            "'a' || char(10) || 'b'"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_identifier() {
+        assert_eq!(sanitize_identifier("my data-2023"), "my_data_2023");
+        assert_eq!(sanitize_identifier("2023"), "_2023");
+        assert_eq!(sanitize_identifier(""), DEFAULT_TABLE_NAME);
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        let name = r#"a"b]c`d"#;
+        assert_eq!(SqlDialect::Generic.quote_identifier(name), r#""a""b]c`d""#);
+        assert_eq!(SqlDialect::Postgres.quote_identifier(name), r#""a""b]c`d""#);
+        assert_eq!(SqlDialect::Sqlite.quote_identifier(name), r#""a""b]c`d""#);
+        assert_eq!(SqlDialect::Mysql.quote_identifier(name), r#"`a"b]c``d`"#);
+        assert_eq!(SqlDialect::Mssql.quote_identifier(name), r#"[a"b]]c`d]"#);
+    }
+
+    #[test]
+    fn test_type_name() {
+        let column_type = |value_type, max_length| ColumnType {
+            value_type,
+            nullable: false,
+            max_length,
         };
-        let contents_iter = if self.has_header {
-            self.contents.iter().skip(1)
-        } else {
-            self.contents.iter().skip(0)
+        let integer = column_type(ValueType::Integer, 3);
+        let short_text = column_type(ValueType::Text, 10);
+        let long_text = column_type(ValueType::Text, MAX_VARCHAR_LENGTH + 1);
+
+        assert_eq!(SqlDialect::Generic.type_name(&integer), "INTEGER");
+        assert_eq!(SqlDialect::Postgres.type_name(&integer), "BIGINT");
+        assert_eq!(
+            SqlDialect::Postgres.type_name(&column_type(ValueType::Real, 3)),
+            "DOUBLE PRECISION"
+        );
+        assert_eq!(
+            SqlDialect::Sqlite.type_name(&column_type(ValueType::Boolean, 4)),
+            "INTEGER"
+        );
+        assert_eq!(
+            SqlDialect::Mssql.type_name(&column_type(ValueType::Boolean, 4)),
+            "BIT"
+        );
+        assert_eq!(
+            SqlDialect::Mysql.type_name(&column_type(ValueType::Timestamp, 19)),
+            "DATETIME"
+        );
+        assert_eq!(SqlDialect::Generic.type_name(&short_text), "VARCHAR(10)");
+        assert_eq!(SqlDialect::Generic.type_name(&long_text), "TEXT");
+        assert_eq!(SqlDialect::Mssql.type_name(&short_text), "NVARCHAR(10)");
+        assert_eq!(SqlDialect::Mssql.type_name(&long_text), "NVARCHAR(MAX)");
+        assert_eq!(SqlDialect::Sqlite.type_name(&short_text), "TEXT");
+    }
+
+    #[test]
+    fn test_insert_conflict() {
+        use SqlDialect::*;
+        use SqlOnConflict::*;
+        let names = ["id".to_string(), "name".to_string()];
+        let insert = |dialect: SqlDialect, on_conflict: SqlOnConflict| {
+            let mut table = SqlDdlTable::new(vec![]);
+            table.dialect(dialect).on_conflict(on_conflict);
+            table
+                .to_insert_prefix()
+                .map(|prefix| (prefix, table.to_insert_suffix(&names)))
         };
-        let contents_rendered = contents_iter
-            .map(|row| format!("('{}')", row.join("','")))
-            .collect::<Vec<String>>()
-            .join(",\n");
 
-        Ok(format!(
-            "INSERT INTO table_name\n({})\nVALUES\n{};\n",
-            header_names.join(","),
-            contents_rendered,
-        ))
+        for dialect in [Generic, Postgres, Mysql, Sqlite, Mssql] {
+            assert_eq!(
+                insert(dialect, Error).unwrap(),
+                ("INSERT INTO", String::new())
+            );
+        }
+        assert_eq!(
+            insert(Postgres, Ignore).unwrap(),
+            ("INSERT INTO", "\nON CONFLICT DO NOTHING".to_string())
+        );
+        assert_eq!(
+            insert(Postgres, Replace).unwrap(),
+            (
+                "INSERT INTO",
+                "\nON CONFLICT (\"id\") DO UPDATE SET \"name\" = EXCLUDED.\"name\"".to_string()
+            )
+        );
+        assert_eq!(
+            insert(Mysql, Ignore).unwrap(),
+            ("INSERT IGNORE INTO", String::new())
+        );
+        assert_eq!(
+            insert(Mysql, Replace).unwrap(),
+            ("REPLACE INTO", String::new())
+        );
+        assert_eq!(
+            insert(Sqlite, Ignore).unwrap(),
+            ("INSERT OR IGNORE INTO", String::new())
+        );
+        assert_eq!(
+            insert(Sqlite, Replace).unwrap(),
+            ("INSERT OR REPLACE INTO", String::new())
+        );
+        for (dialect, on_conflict) in [(Generic, Ignore), (Generic, Replace), (Mssql, Replace)] {
+            assert!(matches!(
+                insert(dialect, on_conflict),
+                Err(SqlDdlError::UnsupportedConflict(_, _))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_create_table_conflict_key() {
+        let contents = to_contents(&[&["id", "name"], &["1", "a"], &["2", ""]]);
+
+        let plain = SqlDdlTable::new(contents.clone())
+            .has_header(true)
+            .to_sql()
+            .unwrap();
+        assert!(plain.starts_with(
+            "CREATE TABLE \"table_name\" (\n    \"id\" INTEGER NOT NULL,\n    \"name\" VARCHAR(1) NULL\n);"
+        ));
+
+        let upsert = SqlDdlTable::new(contents)
+            .has_header(true)
+            .dialect(SqlDialect::Postgres)
+            .on_conflict(SqlOnConflict::Replace)
+            .to_sql()
+            .unwrap();
+        assert!(upsert.starts_with(
+            "CREATE TABLE IF NOT EXISTS \"table_name\" (\n    \"id\" BIGINT PRIMARY KEY,\n"
+        ));
+        assert!(upsert.contains("ON CONFLICT (\"id\") DO UPDATE"));
+    }
+
+    #[test]
+    fn test_upsert_keys() {
+        let contents = to_contents(&[
+            &["name", "qty"],
+            &["bob", "2"],
+            &["ann", "1"],
+            &["bob", "3"],
+        ]);
+        let upsert = |contents: Vec<Vec<String>>| {
+            SqlDdlTable::new(contents)
+                .has_header(true)
+                .dialect(SqlDialect::Postgres)
+                .on_conflict(SqlOnConflict::Replace)
+                .to_sql()
+        };
+
+        assert!(upsert(contents)
+            .unwrap()
+            .contains("VALUES\n('ann', 1),\n('bob', 3)\nON CONFLICT"));
+        assert!(matches!(
+            upsert(to_contents(&[&["name", "qty"], &["", "1"], &["bob", "2"]])),
+            Err(SqlDdlError::NullableKey(name)) if name == "name"
+        ));
+    }
+
+    #[test]
+    fn test_column_names() {
+        let sql = SqlDdlTable::new(to_contents(&[&["id", "id", ""], &["1", "2", "3"]]))
+            .has_header(true)
+            .to_sql()
+            .unwrap();
+        assert!(sql.contains("(\"id\", \"id_2\", \"col_3\")"));
+
+        assert!(matches!(
+            SqlDdlTable::new(vec![]).to_sql(),
+            Err(SqlDdlError::EmptyContents)
+        ));
+    }
+
+    #[test]
+    fn test_sql_batches() {
+        let contents = to_contents(&[&["id"], &["1"], &["2"], &["3"]]);
+
+        let batched = SqlDdlTable::new(contents)
+            .has_header(true)
+            .dialect(SqlDialect::Postgres)
+            .schema(Some("sales".to_string()))
+            .batch_size(Some(2))
+            .transaction(true)
+            .to_sql()
+            .unwrap();
+        assert_eq!(
+            batched,
+            "\
+BEGIN;
+
+CREATE TABLE \"sales\".\"table_name\" (
+    \"id\" BIGINT NOT NULL
+);
+
+INSERT INTO \"sales\".\"table_name\"
+(\"id\")
+VALUES
+(1),
+(2);
+
+INSERT INTO \"sales\".\"table_name\"
+(\"id\")
+VALUES
+(3);
+
+COMMIT;
+"
+        );
+
+        let rows = (0..=1000).map(|i| vec![i.to_string()]).collect();
+        let mssql = SqlDdlTable::new(rows)
+            .dialect(SqlDialect::Mssql)
+            .transaction(true)
+            .to_sql()
+            .unwrap();
+        assert!(mssql.starts_with("BEGIN TRANSACTION;\n"));
+        assert!(mssql.ends_with("COMMIT;\n"));
+        assert_eq!(mssql.matches("INSERT INTO").count(), 2);
+        assert!(mssql.contains("(999);\n\nINSERT INTO [table_name]\n([col_1])\nVALUES\n(1000);"));
+    }
+
+    #[test]
+    fn test_sql_values() {
+        let contents = to_contents(&[&["id", "name", "flag"], &["1", "O'Brien", "true"], &["NA"]]);
+        let sql = SqlDdlTable::new(contents)
+            .has_header(true)
+            .null_values(vec!["NA".to_string()])
+            .to_sql()
+            .unwrap();
+        assert!(sql.contains("\"id\" INTEGER NULL"));
+        assert!(sql.contains("(1, 'O''Brien', TRUE),\n(NULL, NULL, NULL);"));
     }
 }
@@ -2,30 +2,461 @@
 //! Table structures and functions
 //!
 
+use crate::cli_args::{OnError, Ragged};
+use crate::html::{read_html_table, HtmlTableSelector};
 use crate::lines::{
-    Line, LineIgnoreContiguous, LineQuotedIgnoreContiguous, LineQuotedSplitContiguous,
-    LineSplitContiguous, ParseError,
+    numbered_lines, read_lines, Line, LineBoxed, LineFixedWidth, LineIgnoreContiguous,
+    LineMarkdown, LineQuotedIgnoreContiguous, LineQuotedSplitContiguous, LineRegexIgnoreContiguous,
+    LineRegexSplitContiguous, LineSplitContiguous, LineStrIgnoreContiguous, LineStrSplitContiguous,
+    ParseError, Quoting, Records, BOX_VERTICAL_RULES,
 };
+use crate::markdown::Alignment;
+use crate::sniff::{sniff, DEFAULT_SAMPLE_LINES};
+use log::{debug, info, warn};
+use regex::Regex;
+use std::borrow::Borrow;
+use std::collections::{HashSet, VecDeque};
 use std::error::Error;
-use std::path::PathBuf;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use thiserror::Error;
+
+/// Maximum number of characters of a line shown in a diagnostic
+const MAX_SNIPPET_WIDTH: usize = 80;
+
+///
+/// Name a column by its position, for tables without a header.
+///
+pub fn default_column_name(index: usize) -> String {
+    format!("col_{}", index + 1)
+}
+
+///
+/// Make repeated column names unique by adding a number, e.g. a second "name" becomes "name_2".
+/// Used where the names are keys, which must not repeat.
+///
+pub fn unique_column_names(names: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    names
+        .into_iter()
+        .map(|name| {
+            let mut unique = name.clone();
+            let mut count = 1;
+            while !seen.insert(unique.clone()) {
+                count += 1;
+                unique = format!("{}_{}", name, count);
+            }
+            unique
+        })
+        .collect()
+}
+
+///
+/// Name the columns from a header, naming blank names and any columns beyond the end of the
+/// header by position, and making repeated names unique.
+///
+pub fn name_columns(header: &[String], num_columns: usize) -> Vec<String> {
+    unique_column_names(
+        (0..num_columns)
+            .map(|i| match header.get(i) {
+                Some(name) if !name.trim().is_empty() => name.clone(),
+                _ => default_column_name(i),
+            })
+            .collect(),
+    )
+}
+
+///
+/// Get the number of columns needed to represent the rows, i.e. the length of the longest row.
+///
+pub fn num_columns(rows: &[Vec<String>]) -> usize {
+    rows.iter().map(|row| row.len()).max().unwrap_or(0)
+}
+
+///
+/// Get the column names of the rows, wide enough for the longest row. The names are taken from
+/// the first row if it is a header, or None if there is no first row.
+///
+pub fn column_names(rows: &[Vec<String>], has_header: bool) -> Option<Vec<String>> {
+    let num_columns = num_columns(rows);
+    match (has_header, rows.first()) {
+        (false, _) => Some(name_columns(&[], num_columns)),
+        (true, Some(header)) => Some(name_columns(header, num_columns)),
+        (true, None) => None,
+    }
+}
+
+///
+/// Build rows of owned values, for tests.
+///
+#[cfg(test)]
+pub fn to_contents(rows: &[&[&str]]) -> Vec<Vec<String>> {
+    rows.iter()
+        .map(|row| row.iter().map(|v| v.to_string()).collect())
+        .collect()
+}
+
+///
+/// Detect fixed-width column offsets from columns of whitespace that line up across all non-blank
+/// lines. Each offset is the start of a column following such a whitespace column.
+///
+pub fn detect_column_offsets(contents: &str) -> Vec<usize> {
+    let lines = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.chars().collect::<Vec<char>>())
+        .collect::<Vec<_>>();
+    let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+    let blank = (0..width)
+        .map(|i| {
+            lines
+                .iter()
+                .all(|line| line.get(i).is_none_or(|c| c.is_whitespace()))
+        })
+        .collect::<Vec<bool>>();
+    let first = blank.iter().position(|&b| !b).unwrap_or(width);
+
+    (first + 1..width)
+        .filter(|&i| blank[i - 1] && !blank[i])
+        .collect()
+}
+
+///
+/// Get fixed-width column offsets from the words in the first non-blank line. A word starts a new
+/// column if it follows two or more spaces, or if the data lines are all blank just before it, so
+/// that headers such as "CONTAINER ID" are kept together.
+///
+pub fn header_column_offsets(contents: &str) -> Vec<usize> {
+    let mut lines = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.chars().collect::<Vec<char>>());
+    let header = lines.next().unwrap_or_default();
+    let data = lines.collect::<Vec<_>>();
+    let first = header
+        .iter()
+        .position(|c| !c.is_whitespace())
+        .unwrap_or(header.len());
+
+    (first + 1..header.len())
+        .filter(|&i| header[i - 1].is_whitespace() && !header[i].is_whitespace())
+        .filter(|&i| {
+            header[i - 2].is_whitespace()
+                || data
+                    .iter()
+                    .all(|line| line.get(i - 1).is_none_or(|c| c.is_whitespace()))
+        })
+        .collect()
+}
+
+///
+/// Error splitting a line, with the position of the problem in the input.
+///
+#[derive(Error, Debug)]
+#[error("Line {line_number}, column {column}: {source}")]
+pub struct LineError {
+    /// 1-based physical line number where the problem started
+    pub line_number: usize,
+    /// 1-based character column within that line
+    pub column: usize,
+    /// Text of the physical line
+    pub text: String,
+    #[source]
+    pub source: ParseError,
+}
+
+impl LineError {
+    ///
+    /// Locate an error within a record that starts on the given line. Records can span several
+    /// physical lines, so the record-relative column is converted to a physical line and column.
+    ///
+    pub fn new(line_number: usize, record: &str, source: ParseError) -> Self {
+        let offset = record
+            .char_indices()
+            .nth(source.column().saturating_sub(1))
+            .map_or(record.len(), |(i, _)| i);
+        let start = record[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let end = record[offset..]
+            .find('\n')
+            .map_or(record.len(), |i| offset + i);
+        LineError {
+            line_number: line_number + record[..offset].matches('\n').count(),
+            column: record[start..offset].chars().count() + 1,
+            text: record[start..end].to_string(),
+            source,
+        }
+    }
+
+    ///
+    /// Render the error as a diagnostic showing the offending line with a caret under the column.
+    /// Long lines are cut down to a window around the column.
+    ///
+    pub fn diagnostic(&self) -> String {
+        let chars = self
+            .text
+            .chars()
+            .map(|c| if c == '\t' { ' ' } else { c })
+            .collect::<Vec<char>>();
+        let caret = self.column - 1;
+        let first = caret.saturating_sub(MAX_SNIPPET_WIDTH / 2);
+        let first = first.min(chars.len().saturating_sub(MAX_SNIPPET_WIDTH));
+        let last = chars.len().min(first + MAX_SNIPPET_WIDTH);
+        let prefix = if first > 0 { "..." } else { "" };
+        let suffix = if last < chars.len() { "..." } else { "" };
+        let snippet = chars[first..last].iter().collect::<String>();
+
+        let gutter = " ".repeat(self.line_number.to_string().len());
+        format!(
+            "error: {}\n{} --> line {}, column {}\n{} |\n{} | {}{}{}\n{} | {}^\n",
+            self.source,
+            gutter,
+            self.line_number,
+            self.column,
+            gutter,
+            self.line_number,
+            prefix,
+            snippet,
+            suffix,
+            gutter,
+            " ".repeat(prefix.len() + caret - first),
+        )
+    }
+}
+
+///
+/// A record that could not be split into fields, and the reason why.
+///
+#[derive(Debug)]
+pub struct Rejected {
+    pub error: LineError,
+    /// Raw text of the record, which may span several lines
+    pub record: String,
+}
+
+impl Rejected {
+    ///
+    /// Get the number of physical lines in the record.
+    ///
+    pub fn num_lines(&self) -> usize {
+        self.record.lines().count().max(1)
+    }
+}
+
+///
+/// Lines left out of a table, such as a title, comments, or a trailing row count.
+///
+#[derive(Debug, Clone, Default)]
+pub struct LineFilter {
+    /// Number of lines to skip at the start of the contents
+    pub skip_lines: usize,
+    /// Number of lines to skip at the end of the contents
+    pub skip_footer: usize,
+    /// Lines starting with this prefix, ignoring leading whitespace, are skipped
+    pub comment_prefix: Option<String>,
+    /// Whether to skip lines that are empty or only whitespace
+    pub skip_blank_lines: bool,
+}
+
+impl LineFilter {
+    ///
+    /// Whether the text of a line is kept, i.e. it is not a comment or a skipped blank line.
+    ///
+    fn keeps(&self, line: &str) -> bool {
+        !(self.skip_blank_lines && line.trim().is_empty())
+            && self
+                .comment_prefix
+                .as_ref()
+                .is_none_or(|prefix| !line.trim_start().starts_with(prefix.as_str()))
+    }
+
+    ///
+    /// Get the physical lines that are kept, with their line numbers. Lines are read as they are
+    /// needed, and held back only while they could be part of the footer. Trailing blank lines are
+    /// not counted as part of the footer, but are skipped along with it.
+    ///
+    pub fn keep_lines(&self, lines: Records) -> Records {
+        Box::new(FilteredLines {
+            filter: self.clone(),
+            lines,
+            pending: VecDeque::new(),
+            num_non_blank: 0,
+        })
+    }
+
+    ///
+    /// Get the contents without the skipped lines, e.g. for detecting the table layout.
+    ///
+    pub fn apply(&self, contents: &str) -> String {
+        self.keep_lines(numbered_lines(contents))
+            .flatten()
+            .map(|(_, line)| line)
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+///
+/// Physical lines kept by a line filter.
+///
+struct FilteredLines {
+    filter: LineFilter,
+    lines: Records,
+    /// Lines that could still be part of the footer
+    pending: VecDeque<(usize, String)>,
+    /// Number of pending lines that are not blank
+    num_non_blank: usize,
+}
+
+impl Iterator for FilteredLines {
+    type Item = io::Result<(usize, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // A line is not in the footer once enough non-blank lines follow it
+            if let Some((_, line)) = self.pending.front() {
+                let non_blank = !line.trim().is_empty();
+                if self.num_non_blank - usize::from(non_blank) >= self.filter.skip_footer {
+                    self.num_non_blank -= usize::from(non_blank);
+                    let (line_number, line) = self.pending.pop_front()?;
+                    if line_number > self.filter.skip_lines && self.filter.keeps(&line) {
+                        return Some(Ok((line_number, line)));
+                    }
+                    continue;
+                }
+            }
+            match self.lines.next()? {
+                Ok((line_number, line)) => {
+                    self.num_non_blank += usize::from(!line.trim().is_empty());
+                    self.pending.push_back((line_number, line));
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+///
+/// Methods of finding the column boundaries of a fixed-width table.
+///
+#[derive(Debug, Clone)]
+pub enum ColumnBoundaries {
+    /// Detect boundaries from columns of whitespace that line up across all lines
+    Auto,
+    /// Start columns at the words in the header line that follow a gap
+    Header,
+    /// Character offsets at which each column starts
+    Offsets(Vec<usize>),
+}
 
 ///
 /// Enum of all tables with different line types.
 ///
+#[allow(clippy::enum_variant_names)]
 pub enum Table {
     SplitContiguous(TableContent<LineSplitContiguous>),
     IgnoreContiguous(TableContent<LineIgnoreContiguous>),
     QuotedSplitContiguous(TableContent<LineQuotedSplitContiguous>),
     QuotedIgnoreContiguous(TableContent<LineQuotedIgnoreContiguous>),
+    StrSplitContiguous(TableContent<LineStrSplitContiguous>),
+    StrIgnoreContiguous(TableContent<LineStrIgnoreContiguous>),
+    RegexSplitContiguous(TableContent<LineRegexSplitContiguous>),
+    RegexIgnoreContiguous(TableContent<LineRegexIgnoreContiguous>),
+    FixedWidth(TableContent<LineFixedWidth>),
+    Boxed(TableContent<LineBoxed>),
+    Markdown(TableContent<LineMarkdown>),
+    /// Rows read from an HTML table, with whether the first row is marked as a header
+    Html(Vec<Vec<String>>, Option<bool>),
 }
 
 impl Table {
-    pub fn split(&self) -> Result<Vec<Vec<String>>, ParseError> {
+    pub fn split(&self, ragged: &Ragged, on_error: &OnError) -> Result<SplitTable, LineError> {
         match self {
-            Table::SplitContiguous(t) => t.split(),
-            Table::IgnoreContiguous(t) => t.split(),
-            Table::QuotedSplitContiguous(t) => t.split(),
-            Table::QuotedIgnoreContiguous(t) => t.split(),
+            Table::SplitContiguous(t) => t.split(ragged, on_error),
+            Table::IgnoreContiguous(t) => t.split(ragged, on_error),
+            Table::QuotedSplitContiguous(t) => t.split(ragged, on_error),
+            Table::QuotedIgnoreContiguous(t) => t.split(ragged, on_error),
+            Table::StrSplitContiguous(t) => t.split(ragged, on_error),
+            Table::StrIgnoreContiguous(t) => t.split(ragged, on_error),
+            Table::RegexSplitContiguous(t) => t.split(ragged, on_error),
+            Table::RegexIgnoreContiguous(t) => t.split(ragged, on_error),
+            Table::FixedWidth(t) => t.split(ragged, on_error),
+            Table::Boxed(t) => t.split(ragged, on_error),
+            Table::Markdown(t) => t.split(ragged, on_error),
+            Table::Html(rows, has_header) => Ok(SplitTable {
+                rows: rows.clone(),
+                rejected: vec![],
+                has_header: *has_header,
+            }),
+        }
+    }
+
+    ///
+    /// Split each record into fields and pass each row to a function as soon as it is split,
+    /// rather than collecting every row first. The rest of a streamed table is read from its
+    /// input as it is needed. Short rows are not padded.
+    ///
+    pub fn stream_each<F, E>(
+        self,
+        ragged: &Ragged,
+        on_error: &OnError,
+        f: F,
+    ) -> Result<Vec<Rejected>, E>
+    where
+        F: FnMut(Vec<String>) -> Result<(), E>,
+        E: From<LineError> + From<io::Error>,
+    {
+        match self {
+            Table::SplitContiguous(t) => t.stream_each(ragged, on_error, f),
+            Table::IgnoreContiguous(t) => t.stream_each(ragged, on_error, f),
+            Table::QuotedSplitContiguous(t) => t.stream_each(ragged, on_error, f),
+            Table::QuotedIgnoreContiguous(t) => t.stream_each(ragged, on_error, f),
+            Table::StrSplitContiguous(t) => t.stream_each(ragged, on_error, f),
+            Table::StrIgnoreContiguous(t) => t.stream_each(ragged, on_error, f),
+            Table::RegexSplitContiguous(t) => t.stream_each(ragged, on_error, f),
+            Table::RegexIgnoreContiguous(t) => t.stream_each(ragged, on_error, f),
+            Table::FixedWidth(t) => t.stream_each(ragged, on_error, f),
+            Table::Boxed(t) => t.stream_each(ragged, on_error, f),
+            Table::Markdown(t) => t.stream_each(ragged, on_error, f),
+            Table::Html(rows, _) => {
+                rows.into_iter().try_for_each(f)?;
+                Ok(vec![])
+            }
+        }
+    }
+
+    ///
+    /// Whether the first row is a header, if known from the layout of the input.
+    ///
+    pub fn has_header(&self) -> Option<bool> {
+        match self {
+            Table::SplitContiguous(t) => t.has_header,
+            Table::IgnoreContiguous(t) => t.has_header,
+            Table::QuotedSplitContiguous(t) => t.has_header,
+            Table::QuotedIgnoreContiguous(t) => t.has_header,
+            Table::StrSplitContiguous(t) => t.has_header,
+            Table::StrIgnoreContiguous(t) => t.has_header,
+            Table::RegexSplitContiguous(t) => t.has_header,
+            Table::RegexIgnoreContiguous(t) => t.has_header,
+            Table::FixedWidth(t) => t.has_header,
+            Table::Boxed(t) => t.has_header,
+            Table::Markdown(t) => t.has_header,
+            Table::Html(_, has_header) => *has_header,
+        }
+    }
+
+    ///
+    /// Get the column alignments from the alignment row of a markdown table. Other tables have no
+    /// alignments.
+    ///
+    pub fn alignments(&self) -> Vec<Alignment> {
+        match self {
+            Table::Markdown(t) => t
+                .rules
+                .first()
+                .and_then(|(_, rule)| rule.alignments())
+                .unwrap_or_default(),
+            _ => vec![],
         }
     }
 }
@@ -35,16 +466,38 @@ impl Table {
 ///
 pub struct TableBuilder {
     delimiters: Vec<char>,
+    delimiter_strs: Vec<String>,
+    delimiter_regex: Option<Regex>,
     contiguous_delimiters: bool,
     quoted_fields: bool,
+    quoting: Quoting,
+    fixed_width: Option<ColumnBoundaries>,
+    boxed: bool,
+    markdown: bool,
+    html_table: Option<HtmlTableSelector>,
+    auto: bool,
+    sample_lines: usize,
+    line_filter: LineFilter,
+    streaming: bool,
 }
 
 impl Default for TableBuilder {
     fn default() -> Self {
         TableBuilder {
             delimiters: vec![' '],
+            delimiter_strs: vec![],
+            delimiter_regex: None,
             contiguous_delimiters: false,
             quoted_fields: false,
+            quoting: Quoting::default(),
+            fixed_width: None,
+            boxed: false,
+            markdown: false,
+            html_table: None,
+            auto: false,
+            sample_lines: DEFAULT_SAMPLE_LINES,
+            line_filter: LineFilter::default(),
+            streaming: false,
         }
     }
 }
@@ -59,6 +512,16 @@ impl TableBuilder {
         self
     }
 
+    pub fn delimiter_strs(&mut self, delimiter_strs: Vec<String>) -> &mut Self {
+        self.delimiter_strs = delimiter_strs;
+        self
+    }
+
+    pub fn delimiter_regex(&mut self, delimiter_regex: Option<Regex>) -> &mut Self {
+        self.delimiter_regex = delimiter_regex;
+        self
+    }
+
     pub fn contiguous_delimiters(&mut self, contiguous_delimiters: bool) -> &mut Self {
         self.contiguous_delimiters = contiguous_delimiters;
         self
@@ -69,15 +532,189 @@ impl TableBuilder {
         self
     }
 
-    pub fn from_path(&mut self, filepath: &PathBuf) -> Result<Table, Box<dyn Error>> {
+    pub fn quoting(&mut self, quoting: Quoting) -> &mut Self {
+        self.quoting = quoting;
+        self
+    }
+
+    pub fn fixed_width(&mut self, fixed_width: Option<ColumnBoundaries>) -> &mut Self {
+        self.fixed_width = fixed_width;
+        self
+    }
+
+    pub fn boxed(&mut self, boxed: bool) -> &mut Self {
+        self.boxed = boxed;
+        self
+    }
+
+    pub fn markdown(&mut self, markdown: bool) -> &mut Self {
+        self.markdown = markdown;
+        self
+    }
+
+    pub fn html_table(&mut self, html_table: Option<HtmlTableSelector>) -> &mut Self {
+        self.html_table = html_table;
+        self
+    }
+
+    pub fn auto(&mut self, auto: bool) -> &mut Self {
+        self.auto = auto;
+        self
+    }
+
+    pub fn sample_lines(&mut self, sample_lines: usize) -> &mut Self {
+        self.sample_lines = sample_lines;
+        self
+    }
+
+    pub fn line_filter(&mut self, line_filter: LineFilter) -> &mut Self {
+        self.line_filter = line_filter;
+        self
+    }
+
+    ///
+    /// Set to true to read line-based input line by line as the table is streamed, rather than
+    /// reading all of it up front. Input that needs every line to be read, such as HTML or fixed
+    /// width columns detected from the contents, is still read up front.
+    ///
+    pub fn streaming(&mut self, streaming: bool) -> &mut Self {
+        self.streaming = streaming;
+        self
+    }
+
+    ///
+    /// Create the table content from the lines, reading only the start of it when streaming.
+    ///
+    fn content<T: Line>(
+        &self,
+        lines: Records,
+        delimiters: &T::Delimiters,
+        options: T::Options,
+    ) -> io::Result<TableContent<T>> {
+        if self.streaming {
+            TableContent::streaming(lines, delimiters, options, &self.line_filter)
+        } else {
+            TableContent::with_options(lines, delimiters, options, &self.line_filter)
+        }
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_path(&mut self, filepath: &Path) -> Result<Table, Box<dyn Error>> {
+        self.from_reader(File::open(filepath)?)
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_reader<R: Read + 'static>(
+        &mut self,
+        mut reader: R,
+    ) -> Result<Table, Box<dyn Error>> {
         use Table::*;
-        let contents = std::fs::read_to_string(filepath)?;
+        let reads_all = self.html_table.is_some()
+            || self.auto
+            || matches!(
+                self.fixed_width,
+                Some(ColumnBoundaries::Auto | ColumnBoundaries::Header)
+            );
+        let mut contents = String::new();
+        let lines = if self.streaming && !reads_all {
+            debug!("Reading lines as they are streamed");
+            read_lines(BufReader::new(reader))
+        } else {
+            reader.read_to_string(&mut contents)?;
+            debug!("Read {} bytes", contents.len());
+            numbered_lines(&contents)
+        };
+        let filter = &self.line_filter;
+
+        if let Some(boundaries) = &self.fixed_width {
+            let offsets = match boundaries {
+                ColumnBoundaries::Auto => detect_column_offsets(&filter.apply(&contents)),
+                ColumnBoundaries::Header => header_column_offsets(&filter.apply(&contents)),
+                ColumnBoundaries::Offsets(offsets) => offsets.clone(),
+            };
+            debug!("Splitting fixed-width columns at offsets {:?}", offsets);
+            return Ok(FixedWidth(self.content(lines, &offsets[..], ())?));
+        }
+
+        if self.boxed {
+            debug!("Splitting box-drawn cells on {:?}", BOX_VERTICAL_RULES);
+            return Ok(Boxed(self.content(lines, &BOX_VERTICAL_RULES[..], ())?));
+        }
+
+        if let Some(selector) = &self.html_table {
+            let (rows, has_header) = read_html_table(&contents, selector)?;
+            debug!("Read {} rows from HTML table {:?}", rows.len(), selector);
+            return Ok(Html(rows, has_header));
+        }
+
+        if self.markdown {
+            debug!("Splitting markdown cells on unescaped pipes");
+            return Ok(Markdown(self.content(lines, &(), ())?));
+        }
+
+        if self.delimiter_regex.is_some() || !self.delimiter_strs.is_empty() {
+            if self.quoted_fields {
+                warn!("Quoted fields are not supported with string or regex delimiters");
+            }
+            let strs = &self.delimiter_strs[..];
+            let table = match (&self.delimiter_regex, self.contiguous_delimiters) {
+                (Some(regex), false) => RegexSplitContiguous(self.content(lines, regex, ())?),
+                (Some(regex), true) => RegexIgnoreContiguous(self.content(lines, regex, ())?),
+                (None, false) => StrSplitContiguous(self.content(lines, strs, ())?),
+                (None, true) => StrIgnoreContiguous(self.content(lines, strs, ())?),
+            };
+            debug!(
+                "Splitting lines on delimiters {:?}",
+                self.delimiter_regex
+                    .as_ref()
+                    .map_or(format!("{:?}", self.delimiter_strs), |r| r.to_string())
+            );
+            return Ok(table);
+        }
+
+        if self.auto {
+            match sniff(&filter.apply(&contents), self.sample_lines) {
+                Some(sniffed) => {
+                    info!(
+                        "Detected delimiters {:?}, contiguous delimiters {}, quoted fields {} \
+                         (confidence {:.0}%)",
+                        sniffed.delimiters,
+                        sniffed.contiguous_delimiters,
+                        sniffed.quoted_fields,
+                        sniffed.confidence * 100.0
+                    );
+                    self.delimiters = sniffed.delimiters;
+                    self.contiguous_delimiters = sniffed.contiguous_delimiters;
+                    self.quoted_fields = sniffed.quoted_fields;
+                }
+                None => info!("Could not detect delimiters, using configured delimiters"),
+            }
+        }
+
+        let delimiters = &self.delimiters[..];
         let table = match (self.contiguous_delimiters, self.quoted_fields) {
-            (false, false) => SplitContiguous(TableContent::new(&contents, &self.delimiters)),
-            (true, false) => IgnoreContiguous(TableContent::new(&contents, &self.delimiters)),
-            (false, true) => QuotedSplitContiguous(TableContent::new(&contents, &self.delimiters)),
-            (true, true) => QuotedIgnoreContiguous(TableContent::new(&contents, &self.delimiters)),
+            (false, false) => SplitContiguous(self.content(lines, delimiters, ())?),
+            (true, false) => IgnoreContiguous(self.content(lines, delimiters, ())?),
+            (false, true) => {
+                QuotedSplitContiguous(self.content(lines, delimiters, self.quoting.clone())?)
+            }
+            (true, true) => {
+                QuotedIgnoreContiguous(self.content(lines, delimiters, self.quoting.clone())?)
+            }
         };
+        debug!(
+            "Splitting lines with {} contiguous delimiters and {} quoted fields",
+            if self.contiguous_delimiters {
+                "merged"
+            } else {
+                "separate"
+            },
+            if self.quoted_fields {
+                "with"
+            } else {
+                "without"
+            },
+        );
         Ok(table)
     }
 }
@@ -86,25 +723,532 @@ impl TableBuilder {
 /// Table that contains the vec of lines and the required line delimiters.
 ///
 pub struct TableContent<T: Line> {
-    lines: Vec<T>,
-    delimiters: Vec<char>,
+    lines: Vec<(usize, T)>,
+    /// Records not read yet, when the table is streamed
+    unread: Option<Records>,
+    /// Rules drawn between rows, which are not split into fields
+    rules: Vec<(usize, T)>,
+    delimiters: <T::Delimiters as ToOwned>::Owned,
+    options: T::Options,
+    /// Whether the first row is a header, if known from the rules between rows
+    has_header: Option<bool>,
 }
 
 impl<T: Line> TableContent<T> {
-    pub fn new(contents: &str, delimiters: &[char]) -> Self {
-        TableContent {
-            lines: contents.lines().map(T::new).collect(),
-            delimiters: delimiters.to_vec(),
+    pub fn new(contents: &str, delimiters: &T::Delimiters) -> io::Result<Self> {
+        TableContent::with_options(
+            numbered_lines(contents),
+            delimiters,
+            T::Options::default(),
+            &LineFilter::default(),
+        )
+    }
+
+    ///
+    /// Create the table from the physical lines that are kept by the filter. Lines are filtered
+    /// before they are joined into records, so skipped lines cannot open a quoted field.
+    ///
+    pub fn with_options(
+        lines: Records,
+        delimiters: &T::Delimiters,
+        options: T::Options,
+        filter: &LineFilter,
+    ) -> io::Result<Self> {
+        TableContent::read(lines, delimiters, options, filter, true)
+    }
+
+    ///
+    /// Read only the start of the table, far enough to find a header separator. The rest is read
+    /// as the table is streamed.
+    ///
+    pub fn streaming(
+        lines: Records,
+        delimiters: &T::Delimiters,
+        options: T::Options,
+        filter: &LineFilter,
+    ) -> io::Result<Self> {
+        TableContent::read(lines, delimiters, options, filter, false)
+    }
+
+    fn read(
+        lines: Records,
+        delimiters: &T::Delimiters,
+        options: T::Options,
+        filter: &LineFilter,
+        read_all: bool,
+    ) -> io::Result<Self> {
+        let mut records = T::records(filter.keep_lines(lines), &options);
+        let mut lines = Vec::new();
+        let mut rules = Vec::new();
+        let mut num_records = 0;
+        while read_all || lines.len() < 2 {
+            let Some(record) = records.next() else {
+                break;
+            };
+            let (line_number, record) = record?;
+            let line = T::new(&record);
+            if line.is_rule() && (num_records == 1 || T::rules_between_rows()) {
+                rules.push((line_number, line));
+            } else {
+                lines.push((line_number, line));
+            }
+            num_records += 1;
+        }
+
+        // A rule between the first and second rows separates a header from the data
+        let has_header = match (rules.is_empty(), lines.get(0..2)) {
+            (true, _) => None,
+            (false, Some([(first, _), (second, _)])) => {
+                Some(rules.iter().any(|(n, _)| first < n && n < second))
+            }
+            (false, _) => Some(false),
+        };
+        if !rules.is_empty() {
+            debug!(
+                "Dropped {} rule lines, header separator found: {:?}",
+                rules.len(),
+                has_header
+            );
+        }
+
+        Ok(TableContent {
+            lines,
+            unread: (!read_all).then_some(records),
+            rules,
+            delimiters: delimiters.to_owned(),
+            options,
+            has_header,
+        })
+    }
+
+    ///
+    /// Split each record into fields and pass each row to a function as soon as it is split. Rows
+    /// with a different number of fields to the first row are made consistent according to the
+    /// ragged row policy, except for padding, which needs every row. Records that cannot be split
+    /// are handled according to the error policy, and returned unless the policy is to fail.
+    ///
+    fn split_lines<I, L, F, E>(
+        lines: I,
+        delimiters: &T::Delimiters,
+        options: &T::Options,
+        ragged: &Ragged,
+        on_error: &OnError,
+        mut f: F,
+    ) -> Result<Vec<Rejected>, E>
+    where
+        I: IntoIterator<Item = Result<(usize, L), E>>,
+        L: Borrow<T>,
+        F: FnMut(Vec<String>) -> Result<(), E>,
+        E: From<LineError>,
+    {
+        let mut rejected = Vec::new();
+        let mut expected = None;
+        for line in lines {
+            let (line_number, line) = line?;
+            let line = line.borrow();
+            let result = line.split_with(delimiters, options).and_then(|mut row| {
+                let expected = *expected.get_or_insert(row.len());
+                if row.len() != expected {
+                    debug!(
+                        "Line {}: found {} fields, expected {}",
+                        line_number,
+                        row.len(),
+                        expected
+                    );
+                }
+                match ragged {
+                    Ragged::Error if row.len() != expected => {
+                        return Err(ParseError::RaggedRow {
+                            expected,
+                            found: row.len(),
+                        });
+                    }
+                    Ragged::Truncate => row.resize(expected, String::new()),
+                    Ragged::MergeTail if row.len() > expected && expected > 0 => {
+                        let tail = row.split_off(expected - 1);
+                        row.push(tail.join(&line.separator(delimiters)));
+                    }
+                    Ragged::MergeTail => row.resize(expected, String::new()),
+                    _ => {}
+                }
+                Ok(row)
+            });
+            match result {
+                Ok(row) => f(row)?,
+                Err(err) => {
+                    let error = LineError::new(line_number, line.as_str(), err);
+                    debug!("{}", error);
+                    match on_error {
+                        OnError::Fail => return Err(error.into()),
+                        OnError::Skip => {}
+                        OnError::KeepRaw => {
+                            let mut row = vec![line.as_str().to_string()];
+                            row.resize(expected.unwrap_or(1).max(1), String::new());
+                            f(row)?;
+                        }
+                    }
+                    rejected.push(Rejected {
+                        error,
+                        record: line.as_str().to_string(),
+                    });
+                }
+            }
+        }
+        Ok(rejected)
+    }
+
+    ///
+    /// Split each record that has been read into fields, passing each row to a function.
+    ///
+    fn split_each<F, E>(
+        &self,
+        ragged: &Ragged,
+        on_error: &OnError,
+        f: F,
+    ) -> Result<Vec<Rejected>, E>
+    where
+        F: FnMut(Vec<String>) -> Result<(), E>,
+        E: From<LineError>,
+    {
+        Self::split_lines(
+            self.lines.iter().map(|(n, line)| Ok((*n, line))),
+            self.delimiters.borrow(),
+            &self.options,
+            ragged,
+            on_error,
+            f,
+        )
+    }
+
+    ///
+    /// Split each record into fields as it is read, passing each row to a function. Records not
+    /// read yet are read from the input as they are needed.
+    ///
+    pub fn stream_each<F, E>(
+        self,
+        ragged: &Ragged,
+        on_error: &OnError,
+        f: F,
+    ) -> Result<Vec<Rejected>, E>
+    where
+        F: FnMut(Vec<String>) -> Result<(), E>,
+        E: From<LineError> + From<io::Error>,
+    {
+        let TableContent {
+            lines,
+            unread,
+            delimiters,
+            options,
+            ..
+        } = self;
+        let unread = unread
+            .into_iter()
+            .flatten()
+            .map(|record| record.map(|(n, record)| (n, T::new(&record))))
+            .filter(
+                |line| !matches!(line, Ok((_, line)) if line.is_rule() && T::rules_between_rows()),
+            );
+        Self::split_lines(
+            lines
+                .into_iter()
+                .map(Ok)
+                .chain(unread)
+                .map(|line| line.map_err(E::from)),
+            delimiters.borrow(),
+            &options,
+            ragged,
+            on_error,
+            f,
+        )
+    }
+
+    ///
+    /// Split each record into fields, and make rows with a different number of fields to the
+    /// first row consistent according to the ragged row policy. Records that cannot be split are
+    /// handled according to the error policy, and returned alongside the rows unless the policy is
+    /// to fail.
+    ///
+    pub fn split(&self, ragged: &Ragged, on_error: &OnError) -> Result<SplitTable, LineError> {
+        let mut rows: Vec<Vec<String>> = Vec::with_capacity(self.lines.len());
+        let rejected = self.split_each(ragged, on_error, |row| {
+            rows.push(row);
+            Ok::<(), LineError>(())
+        })?;
+
+        if let Ragged::Pad = ragged {
+            let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+            for row in rows.iter_mut() {
+                row.resize(width, String::new());
+            }
+        }
+        debug!("Split {} records into fields", rows.len());
+
+        Ok(SplitTable {
+            rows,
+            rejected,
+            has_header: self.has_header,
+        })
+    }
+}
+
+///
+/// Rows split from a table, and the records that could not be split.
+///
+#[derive(Debug)]
+pub struct SplitTable {
+    pub rows: Vec<Vec<String>>,
+    pub rejected: Vec<Rejected>,
+    /// Whether the first row is a header, if known from the layout of the input
+    pub has_header: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXED_WIDTH: &str = "\
+CONTAINER ID   IMAGE    CREATED
+  4f1a9c2b     nginx    2 days ago
+ a81c          redis    5 weeks ago
+";
+
+    #[test]
+    fn test_unique_column_names() {
+        let names = ["a", "b", "a", "a_2", "a"].map(String::from).to_vec();
+        assert_eq!(
+            unique_column_names(names),
+            vec!["a", "b", "a_2", "a_2_2", "a_3"]
+        );
+    }
+
+    #[test]
+    fn test_column_names() {
+        let rows = to_contents(&[&["id", "", "id"], &["1", "2", "3", "4"]]);
+
+        assert_eq!(
+            column_names(&rows, true).unwrap(),
+            vec!["id", "col_2", "id_2", "col_4"]
+        );
+        assert_eq!(
+            column_names(&rows, false).unwrap(),
+            vec!["col_1", "col_2", "col_3", "col_4"]
+        );
+        assert_eq!(column_names(&[], true), None);
+    }
+
+    #[test]
+    fn test_detect_column_offsets() {
+        assert_eq!(detect_column_offsets(FIXED_WIDTH), vec![15, 24]);
+    }
+
+    #[test]
+    fn test_header_column_offsets() {
+        assert_eq!(header_column_offsets(FIXED_WIDTH), vec![15, 24]);
+        assert_eq!(
+            header_column_offsets("CONTAINER ID   IMAGE\n4f1a9c2b1234   nginx\n"),
+            vec![15]
+        );
+        assert_eq!(header_column_offsets("NAME SIZE\nabc  1\n"), vec![5]);
+        assert_eq!(header_column_offsets("  PID TTY"), vec![6]);
+    }
+
+    #[test]
+    fn test_line_filter() {
+        let s = "Users\n# comment\nid name\n\n1 ann\n  # indented\n(1 row)\n";
+        let filter = LineFilter {
+            skip_lines: 1,
+            skip_footer: 1,
+            comment_prefix: Some("#".to_string()),
+            skip_blank_lines: true,
+        };
+        let table = TableContent::<LineSplitContiguous>::with_options(
+            numbered_lines(s),
+            &[' '],
+            (),
+            &filter,
+        )
+        .unwrap();
+
+        let line_numbers = table.lines.iter().map(|(n, _)| *n).collect::<Vec<_>>();
+        assert_eq!(line_numbers, vec![3, 5]);
+        assert_eq!(filter.apply(s), "id name\n1 ann");
+    }
+
+    #[test]
+    fn test_line_filter_before_records() {
+        let s = "Report \"Q1\n# don't edit\nid,name\n1,a\n(1 row)\n\n";
+        let filter = LineFilter {
+            skip_lines: 1,
+            skip_footer: 1,
+            comment_prefix: Some("#".to_string()),
+            skip_blank_lines: false,
+        };
+        let quoting = Quoting {
+            quotes: vec!['"', '\''],
+            ..Quoting::default()
+        };
+        let table = TableContent::<LineQuotedSplitContiguous>::with_options(
+            numbered_lines(s),
+            &[','],
+            quoting,
+            &filter,
+        )
+        .unwrap();
+
+        let split = table.split(&Ragged::Pad, &OnError::Fail).unwrap();
+        assert_eq!(split.rows, vec![vec!["id", "name"], vec!["1", "a"]]);
+        assert_eq!(table.lines[0].0, 3);
+    }
+
+    #[test]
+    fn test_boxed_table() {
+        let mysql =
+            "+----+-------+\n| id | name  |\n+----+-------+\n| 1  | Smith |\n+----+-------+\n";
+        let psql = " id | name\n----+------\n  1 | Smith\n(1 row)\n\n";
+        let filter = LineFilter {
+            skip_footer: 1,
+            ..LineFilter::default()
+        };
+
+        for table in [
+            TableContent::<LineBoxed>::new(mysql, &BOX_VERTICAL_RULES).unwrap(),
+            TableContent::<LineBoxed>::new(psql, &BOX_VERTICAL_RULES).unwrap(),
+            TableContent::<LineBoxed>::with_options(
+                numbered_lines(psql),
+                &BOX_VERTICAL_RULES,
+                (),
+                &filter,
+            )
+            .unwrap(),
+        ] {
+            let split = table.split(&Ragged::Pad, &OnError::Fail).unwrap();
+            assert_eq!(split.rows, vec![vec!["id", "name"], vec!["1", "Smith"]]);
+            assert_eq!(split.has_header, Some(true));
+        }
+    }
+
+    #[test]
+    fn test_markdown_table() {
+        let s = "Name | Qty\n:--- | ---:\n| a \\| b | 1 |\n| - | - |\n";
+        let table = Table::Markdown(TableContent::new(s, &()).unwrap());
+
+        let split = table.split(&Ragged::Pad, &OnError::Fail).unwrap();
+
+        assert_eq!(
+            split.rows,
+            vec![vec!["Name", "Qty"], vec!["a | b", "1"], vec!["-", "-"]]
+        );
+        assert_eq!(split.has_header, Some(true));
+        assert_eq!(table.alignments(), vec![Alignment::Left, Alignment::Right]);
+    }
+
+    #[test]
+    fn test_markdown_blank_lines() {
+        let trailing = "| a | b |\n|---|---|\n| 1 | 2 |\n\n";
+        let leading = "\n| a | b |\n|---|---|\n| 1 | 2 |\n";
+
+        for s in [trailing, leading] {
+            let table = TableContent::<LineMarkdown>::new(s, &()).unwrap();
+            let split = table.split(&Ragged::Pad, &OnError::Fail).unwrap();
+            assert_eq!(split.rows, vec![vec!["a", "b"], vec!["1", "2"]]);
+            assert_eq!(split.has_header, Some(true));
         }
     }
 
-    pub fn split(&self) -> Result<Vec<Vec<String>>, ParseError> {
-        let contents = self
-            .lines
-            .iter()
-            .map(|line| line.split(&self.delimiters))
-            .collect::<Result<Vec<Vec<String>>, _>>()?;
+    #[test]
+    fn test_line_error_position() {
+        let s = "a,b\nc,\"d\ne\n";
+        let table = TableContent::<LineQuotedSplitContiguous>::new(s, &[',']).unwrap();
+
+        let err = table.split(&Ragged::Pad, &OnError::Fail).unwrap_err();
+
+        assert_eq!((err.line_number, err.column), (2, 3));
+        assert_eq!(err.text, "c,\"d");
+        assert_eq!(
+            err.diagnostic(),
+            "error: Unterminated quote\n  --> line 2, column 3\n  |\n2 | c,\"d\n  |   ^\n"
+        );
+    }
+
+    #[test]
+    fn test_split_ragged() {
+        let s = "PID CMD\n1 sh\n2 sleep 10\n3\n";
+        let table = TableContent::<LineIgnoreContiguous>::new(s, &[' ']).unwrap();
+        let split = |ragged| table.split(&ragged, &OnError::Skip).unwrap();
+
+        assert_eq!(split(Ragged::Pad).rows[3], vec!["3", "", ""]);
+        assert_eq!(split(Ragged::Truncate).rows[2], vec!["2", "sleep"]);
+        assert_eq!(split(Ragged::MergeTail).rows[2], vec!["2", "sleep 10"]);
+        assert_eq!(split(Ragged::MergeTail).rows[3], vec!["3", ""]);
+
+        let errors = split(Ragged::Error);
+        assert_eq!(errors.rows.len(), 2);
+        assert_eq!(errors.rejected[0].error.line_number, 3);
+        assert_eq!(
+            errors.rejected[0].error.to_string(),
+            "Line 3, column 1: Found 3 fields, expected 2"
+        );
+    }
+
+    #[test]
+    fn test_streaming() {
+        let s = " id | name\n----+------\n  1 | ann\n\n  2 | bob\n(2 rows)\n";
+        let filter = LineFilter {
+            skip_footer: 1,
+            skip_blank_lines: true,
+            ..LineFilter::default()
+        };
+        let table = TableContent::<LineBoxed>::streaming(
+            numbered_lines(s),
+            &BOX_VERTICAL_RULES,
+            (),
+            &filter,
+        )
+        .unwrap();
+        assert_eq!(table.lines.len(), 2);
+        assert_eq!(table.has_header, Some(true));
+
+        let mut rows = vec![];
+        table
+            .stream_each(&Ragged::Pad, &OnError::Fail, |row| {
+                rows.push(row);
+                Ok::<(), Box<dyn Error>>(())
+            })
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![vec!["id", "name"], vec!["1", "ann"], vec!["2", "bob"]]
+        );
+    }
+
+    #[test]
+    fn test_split_on_error() {
+        let s = "a b\n\"c d\ne f\n";
+        let table = TableContent::<LineQuotedSplitContiguous>::new(s, &[' ']).unwrap();
+
+        let skipped = table.split(&Ragged::Pad, &OnError::Skip).unwrap();
+        let kept = table.split(&Ragged::Pad, &OnError::KeepRaw).unwrap();
+
+        assert_eq!(skipped.rows, vec![vec!["a", "b"], vec!["e", "f"]]);
+        assert_eq!(skipped.rejected.len(), 1);
+        assert_eq!(skipped.rejected[0].error.line_number, 2);
+        assert_eq!(skipped.rejected[0].record, "\"c d");
+        assert_eq!(
+            kept.rows,
+            vec![vec!["a", "b"], vec!["\"c d", ""], vec!["e", "f"]]
+        );
+    }
+
+    #[test]
+    fn test_rejected_lines() {
+        let s = "a,b\n\"c\nd\",e,f\ng,h\n";
+        let table = TableContent::<LineQuotedSplitContiguous>::new(s, &[',']).unwrap();
+
+        let split = table.split(&Ragged::Error, &OnError::Skip).unwrap();
 
-        Ok(contents)
+        assert_eq!(split.rows, vec![vec!["a", "b"], vec!["g", "h"]]);
+        assert_eq!(split.rejected.len(), 1);
+        assert_eq!(split.rejected[0].record, "\"c\nd\",e,f");
+        assert_eq!(split.rejected[0].num_lines(), 2);
     }
 }
@@ -0,0 +1,184 @@
+//!
+//! TOML generation
+//!
+use crate::json::to_json_string;
+use crate::sqlddl::DEFAULT_TABLE_NAME;
+use crate::table::column_names;
+use crate::types::ValueType;
+use crate::yaml::to_float_literal;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum TomlError {
+    #[error("Empty contents in table")]
+    EmptyContents,
+}
+
+///
+/// Render a key, quoted unless it is a valid bare key.
+///
+fn to_toml_key(key: &str) -> String {
+    let is_bare = !key.is_empty()
+        && key
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-');
+    if is_bare {
+        key.to_string()
+    } else {
+        // JSON strings are valid TOML basic strings
+        to_json_string(key)
+    }
+}
+
+///
+/// Render a value as a TOML integer, float, boolean, or string depending on its inferred type.
+/// TOML has no null, so returns None for missing or empty values.
+///
+fn to_toml_value(value: Option<&String>) -> Option<String> {
+    let value = value?;
+    let trimmed = value.trim();
+    let rendered = match ValueType::of(value)? {
+        ValueType::Boolean => trimmed.to_ascii_lowercase(),
+        ValueType::Integer => trimmed
+            .parse::<i64>()
+            .map_or_else(|_| to_json_string(value), |i| i.to_string()),
+        ValueType::Real => match trimmed.parse::<f64>() {
+            Ok(f) if f.is_finite() => to_float_literal(f),
+            _ => to_json_string(value),
+        },
+        _ => to_json_string(value),
+    };
+    Some(rendered)
+}
+
+///
+/// TOML table, written as an array of tables with one entry per row
+///
+#[derive(Debug)]
+pub struct TomlTable {
+    contents: Vec<Vec<String>>,
+    has_header: bool,
+    table_name: String,
+}
+
+impl TomlTable {
+    ///
+    /// Create a new TOML table from a 2D vector of data.
+    ///
+    pub fn new(contents: Vec<Vec<String>>) -> Self {
+        TomlTable {
+            contents,
+            has_header: false,
+            table_name: DEFAULT_TABLE_NAME.to_string(),
+        }
+    }
+
+    ///
+    /// Set to true to use the first row of data as the keys of each entry. Otherwise, columns are
+    /// named by position.
+    ///
+    pub fn has_header(&mut self, has_header: bool) -> &mut Self {
+        self.has_header = has_header;
+        self
+    }
+
+    ///
+    /// Set the name of the array of tables, used in the `[[table_name]]` header of each entry.
+    ///
+    pub fn table_name(&mut self, table_name: &str) -> &mut Self {
+        self.table_name = table_name.to_string();
+        self
+    }
+
+    ///
+    /// Output the table as a TOML string. Empty values are left out of their entry.
+    ///
+    pub fn to_toml(&self) -> Result<String, TomlError> {
+        let names =
+            column_names(&self.contents, self.has_header).ok_or(TomlError::EmptyContents)?;
+        let table_header = format!("[[{}]]\n", to_toml_key(&self.table_name));
+        let entries = self
+            .contents
+            .iter()
+            .skip(usize::from(self.has_header))
+            .map(|row| {
+                let values = names
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, name)| {
+                        to_toml_value(row.get(i))
+                            .map(|value| format!("{} = {}\n", to_toml_key(name), value))
+                    })
+                    .collect::<String>();
+                format!("{}{}", table_header, values)
+            })
+            .collect::<Vec<String>>();
+
+        Ok(entries.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_toml_key() {
+        assert_eq!(to_toml_key("order_id-2"), "order_id-2");
+        assert_eq!(to_toml_key("unit price"), "\"unit price\"");
+        assert_eq!(to_toml_key("a.b"), "\"a.b\"");
+        assert_eq!(to_toml_key(""), "\"\"");
+    }
+
+    #[test]
+    fn test_to_toml() {
+        let contents = vec![
+            vec![
+                "id".to_string(),
+                "unit price".to_string(),
+                "note".to_string(),
+            ],
+            vec!["1".to_string(), "2".to_string(), "say \"hi\"".to_string()],
+            vec!["007".to_string(), "3.0".to_string(), "".to_string()],
+            vec!["3".to_string(), "False".to_string()],
+        ];
+
+        assert_eq!(
+            TomlTable::new(contents)
+                .has_header(true)
+                .table_name("items")
+                .to_toml()
+                .unwrap(),
+            "\
+[[items]]
+id = 1
+\"unit price\" = 2
+note = \"say \\\"hi\\\"\"
+
+[[items]]
+id = \"007\"
+\"unit price\" = 3.0
+
+[[items]]
+id = 3
+\"unit price\" = false
+"
+        );
+        assert_eq!(
+            TomlTable::new(vec![vec!["x".to_string()]])
+                .to_toml()
+                .unwrap(),
+            "[[table_name]]\ncol_1 = \"x\"\n"
+        );
+        assert_eq!(
+            TomlTable::new(vec![
+                vec!["Sales".to_string(), "Sales".to_string()],
+                vec!["1".to_string(), "2".to_string()],
+            ])
+            .has_header(true)
+            .to_toml()
+            .unwrap(),
+            "[[table_name]]\nSales = 1\nSales_2 = 2\n"
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//!
+//! Value and column type inference
+//!
+
+///
+/// Possible types of a single non-empty value.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    Boolean,
+    Integer,
+    Real,
+    Date,
+    Timestamp,
+    Text,
+}
+
+///
+/// Check that a string is made up of a fixed number of ASCII digits.
+///
+fn is_digits(s: &str, count: usize) -> bool {
+    s.len() == count && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+///
+/// Check for a date in the form YYYY-MM-DD.
+///
+fn is_date(s: &str) -> bool {
+    let parts = s.split('-').collect::<Vec<_>>();
+    match parts[..] {
+        [year, month, day] if is_digits(year, 4) && is_digits(month, 2) && is_digits(day, 2) => {
+            let month = month.parse::<u32>().unwrap_or(0);
+            let day = day.parse::<u32>().unwrap_or(0);
+            (1..=12).contains(&month) && (1..=31).contains(&day)
+        }
+        _ => false,
+    }
+}
+
+///
+/// Check for a time in the form HH:MM[:SS[.fff]] with an optional Z or +HH:MM offset.
+///
+fn is_time(s: &str) -> bool {
+    let s = s.strip_suffix('Z').unwrap_or(s);
+    let s = match s.rfind(['+', '-']) {
+        Some(i) if is_digits(&s[i + 1..].replace(':', ""), 4) => &s[..i],
+        _ => s,
+    };
+    let (s, fraction) = s.split_once('.').unwrap_or((s, "0"));
+    if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    let parts = s.split(':').collect::<Vec<_>>();
+    match parts[..] {
+        [hour, minute] | [hour, minute, _] if !is_digits(hour, 2) || !is_digits(minute, 2) => false,
+        [hour, minute] => hour < "24" && minute < "60",
+        [hour, minute, second] => {
+            hour < "24" && minute < "60" && is_digits(second, 2) && second <= "60"
+        }
+        _ => false,
+    }
+}
+
+impl ValueType {
+    ///
+    /// Infer the type of a single value. Returns None for empty values.
+    ///
+    pub fn of(value: &str) -> Option<ValueType> {
+        let value = value.trim();
+        if value.is_empty() {
+            return None;
+        }
+        let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+        // Leading zeros usually indicate an identifier (e.g. zip code) rather than a number
+        let leading_zero =
+            digits.len() > 1 && digits.starts_with('0') && !digits[1..].starts_with('.');
+        let value_type =
+            if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+                ValueType::Boolean
+            } else if leading_zero {
+                ValueType::Text
+            } else if digits.bytes().all(|b| b.is_ascii_digit()) && value.parse::<i64>().is_ok() {
+                ValueType::Integer
+            } else if digits
+                .bytes()
+                .all(|b| b.is_ascii_digit() || b".eE+-".contains(&b))
+                && digits
+                    .bytes()
+                    .next()
+                    .is_some_and(|b| b.is_ascii_digit() || b == b'.')
+                && value.parse::<f64>().is_ok()
+            {
+                ValueType::Real
+            } else if is_date(value) {
+                ValueType::Date
+            } else if value
+                .split_once(['T', ' '])
+                .is_some_and(|(date, time)| is_date(date) && is_time(time))
+            {
+                ValueType::Timestamp
+            } else {
+                ValueType::Text
+            };
+        Some(value_type)
+    }
+
+    ///
+    /// Combine two value types into the narrowest type able to represent both.
+    ///
+    fn merge(self, other: ValueType) -> ValueType {
+        use ValueType::*;
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Integer, Real) | (Real, Integer) => Real,
+            (Date, Timestamp) | (Timestamp, Date) => Timestamp,
+            _ => Text,
+        }
+    }
+}
+
+///
+/// Inferred type of a column of values.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnType {
+    /// Narrowest type of all non-empty values, or Text if all values are empty
+    pub value_type: ValueType,
+    /// Whether any of the values are empty
+    pub nullable: bool,
+    /// Length of the longest value in characters
+    pub max_length: usize,
+}
+
+impl ColumnType {
+    ///
+    /// Infer the column type by scanning all values in a column.
+    ///
+    pub fn infer<'a, I>(values: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut value_type = None;
+        let mut nullable = false;
+        let mut max_length = 0;
+
+        for value in values {
+            max_length = max_length.max(value.chars().count());
+            match (value_type, ValueType::of(value)) {
+                (_, None) => nullable = true,
+                (None, Some(t)) => value_type = Some(t),
+                (Some(a), Some(b)) => value_type = Some(a.merge(b)),
+            }
+        }
+
+        ColumnType {
+            value_type: value_type.unwrap_or(ValueType::Text),
+            nullable,
+            max_length,
+        }
+    }
+}
+
+///
+/// Detect whether the first row is a header by comparing each of its values to the values in the
+/// rest of the column. A header is likely if text values sit above typed (e.g. numeric) values,
+/// and unlikely if the first row has the same types as the rest of the column or repeats values.
+///
+pub fn detect_header(contents: &[Vec<String>]) -> bool {
+    let Some((first, rest)) = contents.split_first() else {
+        return false;
+    };
+    if rest.is_empty() || first.iter().any(|value| value.trim().is_empty()) {
+        return false;
+    }
+
+    let score: i32 = first
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let column = rest
+                .iter()
+                .filter_map(|row| row.get(i).map(|v| v.as_str()))
+                .collect::<Vec<&str>>();
+            let column_type = ColumnType::infer(column.iter().copied());
+            match (ValueType::of(value), column_type.value_type) {
+                (Some(a), b) if a == b && b != ValueType::Text => -1,
+                (Some(ValueType::Text), b) if b != ValueType::Text => 1,
+                _ if column.contains(&value.as_str()) => -1,
+                _ => 0,
+            }
+        })
+        .sum();
+    score > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::table::to_contents;
+
+    #[test]
+    fn test_value_type_of() {
+        assert_eq!(ValueType::of(""), None);
+        assert_eq!(ValueType::of("TRUE"), Some(ValueType::Boolean));
+        assert_eq!(ValueType::of("-42"), Some(ValueType::Integer));
+        assert_eq!(ValueType::of("007"), Some(ValueType::Text));
+        assert_eq!(ValueType::of("3.5e-2"), Some(ValueType::Real));
+        assert_eq!(ValueType::of("inf"), Some(ValueType::Text));
+        assert_eq!(ValueType::of("2023-02-28"), Some(ValueType::Date));
+        assert_eq!(ValueType::of("2023-13-28"), Some(ValueType::Text));
+        assert_eq!(
+            ValueType::of("2023-02-28T12:30:00.5+01:00"),
+            Some(ValueType::Timestamp)
+        );
+        assert_eq!(
+            ValueType::of("2023-02-28 12:30"),
+            Some(ValueType::Timestamp)
+        );
+        assert_eq!(ValueType::of("2023-02-28 25:30"), Some(ValueType::Text));
+    }
+
+    #[test]
+    fn test_detect_header() {
+        assert!(detect_header(&to_contents(&[
+            &["id", "name"],
+            &["1", "a"],
+            &["2", "b"]
+        ])));
+        assert!(!detect_header(&to_contents(&[
+            &["0", "x"],
+            &["1", "a"],
+            &["2", "b"]
+        ])));
+        assert!(!detect_header(&to_contents(&[
+            &["a", "b"],
+            &["c", "d"],
+            &["a", "e"]
+        ])));
+        assert!(!detect_header(&to_contents(&[&["id", "name"]])));
+    }
+
+    #[test]
+    fn test_column_type_infer() {
+        let column = ColumnType::infer(["1", "", "2.5"]);
+        assert_eq!(column.value_type, ValueType::Real);
+        assert!(column.nullable);
+        assert_eq!(column.max_length, 3);
+
+        let column = ColumnType::infer(["2023-01-01", "2023-01-01 10:00", "x"]);
+        assert_eq!(column.value_type, ValueType::Text);
+        assert!(!column.nullable);
+    }
+}
@@ -0,0 +1,212 @@
+//!
+//! YAML generation
+//!
+use crate::json::to_json_string;
+use crate::table::column_names;
+use crate::types::ValueType;
+use thiserror::Error;
+
+/// Plain scalars that YAML 1.1 or 1.2 parsers read as null or a boolean
+const RESERVED_WORDS: [&str; 10] = [
+    "null", "~", "true", "false", "yes", "no", "on", "off", "y", "n",
+];
+
+#[derive(Error, Debug)]
+pub enum YamlError {
+    #[error("Empty contents in table")]
+    EmptyContents,
+}
+
+///
+/// Check whether a string can be written as a plain (unquoted) scalar and read back as the same
+/// string. Anything that could be read as a number, boolean, null, date, or YAML syntax is not.
+///
+fn is_plain_yaml(value: &str) -> bool {
+    let Some(first) = value.chars().next() else {
+        return false;
+    };
+    !(RESERVED_WORDS.iter().any(|w| value.eq_ignore_ascii_case(w))
+        || first.is_ascii_digit()
+        || first.is_whitespace()
+        || "-?:,[]{}#&*!|>'\"%@`+.".contains(first)
+        || value.ends_with(char::is_whitespace)
+        || value.ends_with(':')
+        || value.contains(": ")
+        || value.contains(" #")
+        || value.chars().any(char::is_control))
+}
+
+///
+/// Render a string as a YAML scalar, double quoted when it is not safe as a plain scalar.
+///
+pub fn to_yaml_string(value: &str) -> String {
+    if is_plain_yaml(value) {
+        value.to_string()
+    } else {
+        // JSON strings are valid YAML double quoted scalars
+        to_json_string(value)
+    }
+}
+
+///
+/// Render a float so it is read back as a float, not an integer.
+///
+pub fn to_float_literal(value: f64) -> String {
+    let rendered = value.to_string();
+    if rendered.contains('.') {
+        rendered
+    } else {
+        format!("{}.0", rendered)
+    }
+}
+
+///
+/// Render an optional value as a YAML number, boolean, or string depending on its inferred type,
+/// or null when missing or empty.
+///
+fn to_yaml_value(value: Option<&String>) -> String {
+    let Some(value) = value else {
+        return "null".to_string();
+    };
+    let trimmed = value.trim();
+    match ValueType::of(value) {
+        None => "null".to_string(),
+        Some(ValueType::Boolean) => trimmed.to_ascii_lowercase(),
+        Some(ValueType::Integer) => trimmed
+            .parse::<i64>()
+            .map_or_else(|_| to_yaml_string(value), |i| i.to_string()),
+        Some(ValueType::Real) => match trimmed.parse::<f64>() {
+            Ok(f) if f.is_finite() => to_float_literal(f),
+            _ => to_yaml_string(value),
+        },
+        Some(_) => to_yaml_string(value),
+    }
+}
+
+///
+/// YAML table, written as a sequence of mappings
+///
+#[derive(Debug)]
+pub struct YamlTable {
+    contents: Vec<Vec<String>>,
+    has_header: bool,
+}
+
+impl YamlTable {
+    ///
+    /// Create a new YAML table from a 2D vector of data.
+    ///
+    pub fn new(contents: Vec<Vec<String>>) -> Self {
+        YamlTable {
+            contents,
+            has_header: false,
+        }
+    }
+
+    ///
+    /// Set to true to use the first row of data as the keys of each mapping. Otherwise, columns
+    /// are named by position.
+    ///
+    pub fn has_header(&mut self, has_header: bool) -> &mut Self {
+        self.has_header = has_header;
+        self
+    }
+
+    ///
+    /// Output the table as a YAML string.
+    ///
+    pub fn to_yaml(&self) -> Result<String, YamlError> {
+        let names =
+            column_names(&self.contents, self.has_header).ok_or(YamlError::EmptyContents)?;
+        let rows = self
+            .contents
+            .iter()
+            .skip(usize::from(self.has_header))
+            .map(|row| {
+                if names.is_empty() {
+                    return "- {}\n".to_string();
+                }
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(i, name)| {
+                        let indicator = if i == 0 { "-" } else { " " };
+                        format!(
+                            "{} {}: {}\n",
+                            indicator,
+                            to_yaml_string(name),
+                            to_yaml_value(row.get(i))
+                        )
+                    })
+                    .collect::<String>()
+            })
+            .collect::<String>();
+
+        if rows.is_empty() {
+            Ok("[]\n".to_string())
+        } else {
+            Ok(rows)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_yaml_string() {
+        assert_eq!(to_yaml_string("plain text"), "plain text");
+        assert_eq!(to_yaml_string("a,b"), "a,b");
+        assert_eq!(to_yaml_string("yes"), "\"yes\"");
+        assert_eq!(to_yaml_string("~"), "\"~\"");
+        assert_eq!(to_yaml_string("007"), "\"007\"");
+        assert_eq!(to_yaml_string("2024-01-31"), "\"2024-01-31\"");
+        assert_eq!(to_yaml_string("-x"), "\"-x\"");
+        assert_eq!(to_yaml_string("key: value"), "\"key: value\"");
+        assert_eq!(to_yaml_string("a #b"), "\"a #b\"");
+        assert_eq!(to_yaml_string(" padded"), "\" padded\"");
+        assert_eq!(to_yaml_string("two\nlines"), "\"two\\nlines\"");
+        assert_eq!(to_yaml_string(""), "\"\"");
+    }
+
+    #[test]
+    fn test_to_yaml() {
+        let contents = vec![
+            vec!["id".to_string(), "price".to_string(), "note".to_string()],
+            vec!["1".to_string(), "2".to_string(), "on".to_string()],
+            vec!["2".to_string(), "3.5".to_string(), "".to_string()],
+        ];
+
+        assert_eq!(
+            YamlTable::new(contents.clone())
+                .has_header(true)
+                .to_yaml()
+                .unwrap(),
+            "- id: 1\n  price: 2\n  note: \"on\"\n- id: 2\n  price: 3.5\n  note: null\n"
+        );
+        assert_eq!(
+            YamlTable::new(contents[..1].to_vec())
+                .has_header(true)
+                .to_yaml()
+                .unwrap(),
+            "[]\n"
+        );
+        assert_eq!(
+            YamlTable::new(vec![vec!["TRUE".to_string(), "1e3".to_string()]])
+                .to_yaml()
+                .unwrap(),
+            "- col_1: true\n  col_2: 1000.0\n"
+        );
+        assert_eq!(
+            YamlTable::new(vec![
+                vec!["Sales".to_string(), "Sales".to_string()],
+                vec!["1".to_string(), "2".to_string()],
+            ])
+            .has_header(true)
+            .to_yaml()
+            .unwrap(),
+            "- Sales: 1\n  Sales_2: 2\n"
+        );
+    }
+}